@@ -1,9 +1,18 @@
-use godot::{classes::Engine, obj::WithBaseField, prelude::*};
+use godot::{
+    classes::{Engine, Script},
+    obj::WithBaseField,
+    prelude::*,
+};
 
-use crate::engine_helpers::engine;
+use crate::engine_helpers::{engine, CrateSingleton};
 
 pub fn register() {
-    Engine::singleton().register_singleton(Components::ID, &Components::new_alloc());
+    let components = Components::new_alloc();
+    Engine::singleton().register_singleton(Components::ID, &components);
+    engine::call_when_tree_exists(&Callable::from_object_method(
+        &components,
+        "schedule_systems",
+    ));
 }
 pub fn unregister() {
     Engine::singleton().unregister_singleton(Components::ID);
@@ -35,13 +44,30 @@ where
     T: GodotClass + Inherits<Node>,
 {
     fn on_ready(&mut self) {
-        self.base_mut()
-            .clone()
-            .upcast::<Node>()
-            .add_to_group(Self::class_name().to_gstring().arg());
+        let mut node = self.base_mut().clone().upcast::<Node>();
+        for class in component_classes(&node) {
+            node.add_to_group(class.arg());
+        }
     }
 }
 
+/// Every class name a component is known by, from the most derived script `class_name` down to the rust class it is built on
+///
+/// Components are grouped under each of these so a query for a base class also finds all derived components
+pub fn component_classes(node: &Gd<Node>) -> Vec<GString> {
+    let mut classes = Vec::new();
+    let mut script = node.get_script().try_to::<Gd<Script>>().ok();
+    while let Some(current) = script {
+        let name = current.get_global_name();
+        if !name.is_empty() {
+            classes.push(name.into());
+        }
+        script = current.get_base_script();
+    }
+    classes.push(node.get_class());
+    classes
+}
+
 impl RustyComponent<Node> for ComponentNode {}
 impl RustyComponent<Node2D> for ComponentNode2D {}
 impl RustyComponent<Node3D> for ComponentNode3D {}
@@ -71,20 +97,23 @@ impl INode3D for ComponentNode3D {
 #[class(base=Object, init, tool)]
 /// A utility singleton for performing component-oriented operations
 pub struct Components {
+    systems: Vec<Gd<ComponentSystem>>,
     base: Base<Object>,
 }
 
+impl CrateSingleton for Components {
+    const ID: &'static str = "Components";
+}
+
 #[godot_api]
 impl Components {
-    pub const ID: &'static str = "Components";
-
     #[func]
     /// Tries to get a component from the target assuming components are an immediate child of the target
     ///
-    /// Returns null if target of class cannot be found
+    /// Returns null if target of class cannot be found. Components deriving from the class (including script classes) also match
     pub fn get_component(target: Gd<Node>, class: GString) -> Option<Gd<Node>> {
         for child in target.get_children().iter_shared() {
-            if child.get_class() == class {
+            if component_classes(&child).contains(&class) {
                 return Some(child);
             }
         }
@@ -101,6 +130,134 @@ impl Components {
         };
         scene.get_nodes_in_group(class.arg())
     }
+
+    #[func]
+    /// Adds a system to be run every frame (or physics tick) over its component query
+    pub fn register_system(&mut self, system: Gd<ComponentSystem>) {
+        if self.systems.contains(&system) {
+            return;
+        }
+        self.systems.push(system);
+    }
+
+    #[func]
+    /// Removes a previously registered system. Does nothing if the system was never registered
+    pub fn unregister_system(&mut self, system: Gd<ComponentSystem>) {
+        self.systems.retain(|s| *s != system);
+    }
+
+    #[func]
+    /// All currently registered systems in the order they will be run
+    pub fn get_systems(&self) -> Array<Gd<ComponentSystem>> {
+        self.sorted_systems().into_iter().collect()
+    }
+
+    #[func(gd_self)]
+    fn tick_process_systems(this: Gd<Self>) {
+        let Some(delta) = engine::get_scene_tree()
+            .and_then(|tree| tree.get_root())
+            .map(|root| root.get_process_delta_time())
+        else {
+            return;
+        };
+        Self::run_systems(this, SystemTick::Process, delta);
+    }
+
+    #[func(gd_self)]
+    fn tick_physics_systems(this: Gd<Self>) {
+        let Some(delta) = engine::get_scene_tree()
+            .and_then(|tree| tree.get_root())
+            .map(|root| root.get_physics_process_delta_time())
+        else {
+            return;
+        };
+        Self::run_systems(this, SystemTick::Physics, delta);
+    }
+
+    /// Runs every enabled system for the given tick. No binding to the singleton is held while systems run, so systems are free to (un)register other systems
+    fn run_systems(this: Gd<Self>, tick: SystemTick, delta: f64) {
+        let systems = this.bind().sorted_systems();
+        for system in systems {
+            let query = {
+                let sys = system.bind();
+                if !sys.enabled || sys.tick != tick {
+                    continue;
+                }
+                sys.query.clone()
+            };
+            ComponentSystem::system_tick(system, Self::get_all_components(query), delta);
+        }
+    }
+
+    fn sorted_systems(&self) -> Vec<Gd<ComponentSystem>> {
+        let mut systems = self.systems.clone();
+        systems.sort_by_key(|s| s.bind().order);
+        systems
+    }
+
+    #[func]
+    /// Hooks the system ticks into the scene tree. Systems only run in game
+    fn schedule_systems(&mut self) {
+        if Engine::singleton().is_editor_hint() {
+            return;
+        }
+        let Some(mut tree) = engine::get_scene_tree() else {
+            return;
+        };
+        let gd = self.to_gd();
+        tree.connect(
+            "process_frame",
+            &Callable::from_object_method(&gd, "tick_process_systems"),
+        );
+        tree.connect(
+            "physics_frame",
+            &Callable::from_object_method(&gd, "tick_physics_systems"),
+        );
+    }
+}
+
+#[derive(GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[godot(via=i32)]
+/// Which loop a [ComponentSystem] is run on
+pub enum SystemTick {
+    #[default]
+    Process,
+    Physics,
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, init)]
+/// A batched piece of logic that runs over every component of a class each frame, registered through [Components]
+///
+/// Extend this in GDScript and override `_system_tick`, or use [RustyComponents::add_system] from rust
+pub struct ComponentSystem {
+    #[export]
+    /// The component class name to query for. See [Components::get_all_components]
+    query: GString,
+    #[export]
+    /// Whether this runs during the process or physics loop
+    tick: SystemTick,
+    #[export]
+    /// Systems with a lower order are run first
+    order: i32,
+    #[export]
+    #[init(val = true)]
+    /// Disabled systems stay registered but are skipped
+    enabled: bool,
+    callback: Option<Callable>,
+    base: Base<Resource>,
+}
+
+#[godot_api]
+impl ComponentSystem {
+    #[func(virtual, gd_self)]
+    /// Called once per tick with all components matching the query
+    pub fn system_tick(this: Gd<Self>, components: Array<Gd<Node>>, delta: f64) {
+        let Some(callback) = this.bind().callback.clone() else {
+            return;
+        };
+        callback.callv(&varray![components, delta]);
+    }
 }
 
 pub struct RustyComponents;
@@ -122,6 +279,49 @@ impl RustyComponents {
         None
     }
 
+    /// Registers a rust closure as a [ComponentSystem] running over every component of type `T`
+    ///
+    /// Returns the created system so it can be reordered, disabled or unregistered later
+    pub fn add_system<T, F>(
+        tick: SystemTick,
+        order: i32,
+        mut system: F,
+    ) -> Option<Gd<ComponentSystem>>
+    where
+        T: GodotClass + Inherits<Node>,
+        F: FnMut(Vec<Gd<T>>, f64) + Send + Sync + 'static,
+    {
+        let mut components = Components::singleton()?;
+        let callable = Callable::from_fn(
+            format!("{}System", T::class_name()),
+            move |args: &[&Variant]| {
+                let (Some(nodes), Some(delta)) = (args.first(), args.get(1)) else {
+                    return Err(());
+                };
+                let nodes = nodes.try_to::<Array<Gd<Node>>>().map_err(|_| ())?;
+                let delta = delta.try_to::<f64>().map_err(|_| ())?;
+                system(
+                    nodes
+                        .iter_shared()
+                        .filter_map(|n| n.try_cast::<T>().ok())
+                        .collect(),
+                    delta,
+                );
+                Ok(Variant::nil())
+            },
+        );
+        let mut sys = ComponentSystem::new_gd();
+        {
+            let mut bound = sys.bind_mut();
+            bound.query = T::class_name().to_gstring();
+            bound.tick = tick;
+            bound.order = order;
+            bound.callback = Some(callable);
+        }
+        components.bind_mut().register_system(sys.clone());
+        Some(sys)
+    }
+
     pub fn get_all_components<T>() -> Option<Vec<Gd<T>>>
     where
         T: GodotClass + Inherits<Node>,
@@ -1,6 +1,30 @@
+use godot::{classes::Engine, prelude::*};
+
+/// A class registered with the engine as a singleton under [ID](CrateSingleton::ID)
+pub trait CrateSingleton: GodotClass + Inherits<Object> {
+    const ID: &'static str;
+
+    /// Fetches the registered singleton instance
+    fn singleton() -> Option<Gd<Self>> {
+        Engine::singleton()
+            .get_singleton(Self::ID)
+            .and_then(|obj| obj.try_cast::<Self>().ok())
+    }
+}
+
+/// Like `godot_print!`, but only printed when the engine runs with `--verbose`
+macro_rules! print_verbose {
+    ($($args:tt)*) => {
+        godot::global::print_verbose(&[godot::meta::ToGodot::to_variant(&format!($($args)*))])
+    };
+}
+pub(crate) use print_verbose;
+
 pub mod engine {
     use godot::{
+        builtin::Callable,
         classes::{Engine, SceneTree, Viewport},
+        meta::ToGodot,
         obj::Gd,
     };
     pub fn get_scene_tree() -> Option<Gd<SceneTree>> {
@@ -13,4 +37,30 @@ pub mod engine {
     pub fn get_viewport() -> Option<Gd<Viewport>> {
         get_scene_tree()?.get_root()?.get_viewport()
     }
+
+    /// Calls `callable` on the first frame, once the scene tree exists. Singletons are registered before the tree is created, so anything they need from it is set up through this
+    pub fn call_when_tree_exists(callable: &Callable) {
+        // `Callable::call_deferred` is vararg and not exposed on the rust side
+        callable.to_variant().call("call_deferred", &[]);
+    }
+
+    /// The root viewport and every `SubViewport` in the tree, skipping those with 3D disabled
+    pub fn get_3d_viewports() -> Vec<Gd<Viewport>> {
+        let Some(root) = get_scene_tree().and_then(|tree| tree.get_root()) else {
+            return Vec::new();
+        };
+        let sub_viewports = root
+            .find_children_ex("*")
+            .type_("SubViewport")
+            .owned(false)
+            .done();
+        std::iter::once(root.upcast::<Viewport>())
+            .chain(
+                sub_viewports
+                    .iter_shared()
+                    .filter_map(|node| node.try_cast::<Viewport>().ok()),
+            )
+            .filter(|viewport| !viewport.is_3d_disabled())
+            .collect()
+    }
 }
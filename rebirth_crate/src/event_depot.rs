@@ -2,6 +2,8 @@ use std::{collections::HashMap, path::Path};
 
 use godot::{classes::Engine, prelude::*};
 
+use crate::engine_helpers::CrateSingleton;
+
 pub fn register() {
     Engine::singleton().register_singleton(EventDepot::ID, &EventDepot::new_alloc());
 }
@@ -14,7 +16,7 @@ pub fn unregister() {
 #[class(base=Resource, init, tool)]
 /// The base of all events compatible with the [EventDepot] singleton.
 /// This allows management of events being emitted by different elements to be done mostly from the GUI, with some code needed.
-struct EventResource {
+pub struct EventResource {
     base: Base<Resource>,
 
     #[export]
@@ -27,7 +29,7 @@ struct EventResource {
 #[derive(GodotClass)]
 #[class(base=Resource, init, tool)]
 /// An intermediary for triggering an event in the [EventDepot]
-struct EventArgs {
+pub struct EventArgs {
     #[var(get,set=set_event)]
     #[export]
     /// The event to be triggered
@@ -45,15 +47,17 @@ struct EventArgs {
 /// This singleton is intended to be used for registering and calling an [Observer Pattern](https://en.wikipedia.org/wiki/Observer_pattern) without the use of godot signals. Mainly because signals cause too-close binding of different elements in the scene-tree.
 ///
 ///
-struct EventDepot {
+pub struct EventDepot {
     busses: HashMap<Gd<EventResource>, Vec<Callable>>,
     base: Base<Object>,
 }
 
+impl CrateSingleton for EventDepot {
+    const ID: &'static str = "EventDepot";
+}
+
 #[godot_api]
 impl EventDepot {
-    pub const ID: &'static str = "EventDepot";
-
     #[func]
     /// Triggers an event using the intermediary [EventArgs]
     pub fn trigger(&self, event_args: Gd<EventArgs>) {
@@ -0,0 +1,272 @@
+use godot::builtin::Side;
+use godot::classes::{
+    control::{LayoutPreset, MouseFilter},
+    CanvasLayer, ColorRect, Control, Shader, ShaderMaterial,
+};
+use godot::prelude::*;
+
+use crate::engine_helpers::engine;
+
+#[derive(GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[godot(via=i32)]
+/// Which color vision deficiency the screen is corrected for
+pub enum ColorblindFilter {
+    #[default]
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+#[derive(GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[godot(via=i32)]
+pub enum SubtitleSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+    ExtraLarge,
+}
+
+impl SubtitleSize {
+    fn font_size(self) -> i32 {
+        match self {
+            SubtitleSize::Small => 16,
+            SubtitleSize::Medium => 22,
+            SubtitleSize::Large => 30,
+            SubtitleSize::ExtraLarge => 40,
+        }
+    }
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, tool)]
+/// Accessibility options
+///
+/// The UI scale and colorblind filter are applied directly. Everything else is up to gameplay code, which can read the values or connect to the signals emitted each time the settings are applied. The signals are emitted at the end of the frame, so handlers are free to read [GameSettings](super::GameSettings)
+pub struct AccessibilitySettings {
+    base: Base<Resource>,
+    //
+    //  Subtitles
+    //
+    #[export]
+    subtitle_size: SubtitleSize,
+    #[export(range = (0.0, 1.0))]
+    /// Opacity of the box behind subtitles. 0 hides it
+    subtitle_background: f32,
+    //
+    //  Vision
+    //
+    #[export]
+    colorblind_filter: ColorblindFilter,
+    #[export(range = (0.0, 1.0))]
+    /// Multiplies the strength of every camera shake. 0 disables shake
+    camera_shake_scale: f32,
+    #[export(range = (60.0, 120.0))]
+    /// Vertical field of view in degrees
+    field_of_view: f32,
+    #[export]
+    head_bob_enabled: bool,
+    //
+    //  Interaction
+    //
+    #[export]
+    /// Whether actions that are normally held, e.g. hold interactions, are toggled by a press instead
+    toggle_hold_actions: bool,
+    #[export(range = (0.5, 2.0))]
+    /// Scales every [Control] in the `ui_scale` group, which should be full screen UI roots like the HUD. The 3D view is left alone so its resolution doesn't change
+    ui_scale: f32,
+
+    /// The colorblind filter once created. It is only added to the tree at the end of the frame, so it can't be looked up by name until then
+    filter_layer: Option<Gd<CanvasLayer>>,
+}
+
+#[godot_api]
+impl IResource for AccessibilitySettings {
+    fn init(base: Base<Self::Base>) -> Self {
+        Self {
+            base,
+            subtitle_size: SubtitleSize::Medium,
+            subtitle_background: 0.5,
+            colorblind_filter: ColorblindFilter::None,
+            camera_shake_scale: 1.0,
+            field_of_view: 75.0,
+            head_bob_enabled: true,
+            toggle_hold_actions: false,
+            ui_scale: 1.0,
+            filter_layer: None,
+        }
+    }
+}
+
+#[godot_api]
+impl AccessibilitySettings {
+    const FILTER_NODE: &'static str = "ColorblindFilter";
+    const FILTER_LAYER: i32 = 128;
+    const UI_SCALE_GROUP: &'static str = "ui_scale";
+
+    #[signal]
+    fn subtitles_changed(font_size: i32, background_opacity: f32) {}
+
+    #[signal]
+    fn colorblind_filter_changed(filter: ColorblindFilter) {}
+
+    #[signal]
+    fn camera_shake_scale_changed(scale: f32) {}
+
+    #[signal]
+    fn field_of_view_changed(degrees: f32) {}
+
+    #[signal]
+    fn head_bob_changed(enabled: bool) {}
+
+    #[signal]
+    fn toggle_hold_actions_changed(enabled: bool) {}
+
+    #[signal]
+    fn ui_scale_changed(scale: f32) {}
+
+    #[func]
+    /// Scales the UI, updates the colorblind filter and emits every signal with the current values
+    pub fn apply(&mut self) {
+        self.apply_ui_scale();
+        self.apply_colorblind_filter();
+
+        let subtitles = [
+            self.get_subtitle_font_size().to_variant(),
+            self.subtitle_background.to_variant(),
+        ];
+        let filter = self.colorblind_filter.to_variant();
+        let shake = self.camera_shake_scale.to_variant();
+        let fov = self.field_of_view.to_variant();
+        let head_bob = self.head_bob_enabled.to_variant();
+        let toggle = self.toggle_hold_actions.to_variant();
+        let ui_scale = self.ui_scale.to_variant();
+
+        // Deferred since this runs while GameSettings is bound, which handlers would bind again
+        self.emit_deferred("subtitles_changed", &subtitles);
+        self.emit_deferred("colorblind_filter_changed", &[filter]);
+        self.emit_deferred("camera_shake_scale_changed", &[shake]);
+        self.emit_deferred("field_of_view_changed", &[fov]);
+        self.emit_deferred("head_bob_changed", &[head_bob]);
+        self.emit_deferred("toggle_hold_actions_changed", &[toggle]);
+        self.emit_deferred("ui_scale_changed", &[ui_scale]);
+    }
+
+    #[func]
+    /// The subtitle font size in pixels, before UI scaling
+    pub fn get_subtitle_font_size(&self) -> i32 {
+        self.subtitle_size.font_size()
+    }
+
+    fn emit_deferred(&mut self, signal: &str, args: &[Variant]) {
+        let mut call_args = vec![signal.to_variant()];
+        call_args.extend_from_slice(args);
+        self.base_mut().call_deferred("emit_signal", &call_args);
+    }
+
+    /// Scales the UI roots while keeping them covering their parent, by anchoring them to the matching fraction of it
+    fn apply_ui_scale(&self) {
+        let Some(mut tree) = engine::get_scene_tree() else {
+            return;
+        };
+        let scale = self.ui_scale.max(0.01);
+        for node in tree.get_nodes_in_group(Self::UI_SCALE_GROUP).iter_shared() {
+            let Ok(mut control) = node.try_cast::<Control>() else {
+                continue;
+            };
+            control.set_pivot_offset(Vector2::ZERO);
+            control.set_scale(Vector2::splat(scale));
+            control.set_anchor_and_offset(Side::LEFT, 0.0, 0.0);
+            control.set_anchor_and_offset(Side::TOP, 0.0, 0.0);
+            control.set_anchor_and_offset(Side::RIGHT, 1.0 / scale, 0.0);
+            control.set_anchor_and_offset(Side::BOTTOM, 1.0 / scale, 0.0);
+        }
+    }
+
+    /// Shows a full screen daltonization pass above everything else, creating it on first use
+    fn apply_colorblind_filter(&mut self) {
+        let Some(mut root) = engine::get_scene_tree().and_then(|tree| tree.get_root()) else {
+            return;
+        };
+        let existing = self
+            .filter_layer
+            .clone()
+            .filter(|layer| layer.is_instance_valid())
+            .or_else(|| {
+                root.get_node_or_null(Self::FILTER_NODE)
+                    .and_then(|layer| layer.try_cast::<CanvasLayer>().ok())
+            });
+        let mut layer = match existing {
+            Some(layer) => layer,
+            None if self.colorblind_filter == ColorblindFilter::None => return,
+            None => {
+                let layer = Self::make_filter_layer();
+                root.call_deferred("add_child", &[layer.to_variant()]);
+                layer
+            }
+        };
+        self.filter_layer = Some(layer.clone());
+        layer.set_visible(self.colorblind_filter != ColorblindFilter::None);
+        let Some(mut material) = layer
+            .get_child(0)
+            .and_then(|rect| rect.try_cast::<ColorRect>().ok())
+            .and_then(|rect| rect.get_material())
+            .and_then(|material| material.try_cast::<ShaderMaterial>().ok())
+        else {
+            return;
+        };
+        material.set_shader_parameter("mode", &self.colorblind_filter.to_variant());
+    }
+
+    fn make_filter_layer() -> Gd<CanvasLayer> {
+        let mut shader = Shader::new_gd();
+        shader.set_code(COLORBLIND_SHADER);
+        let mut material = ShaderMaterial::new_gd();
+        material.set_shader(&shader);
+
+        let mut rect = ColorRect::new_alloc();
+        rect.set_anchors_preset(LayoutPreset::FULL_RECT);
+        rect.set_mouse_filter(MouseFilter::IGNORE);
+        rect.set_material(&material);
+
+        let mut layer = CanvasLayer::new_alloc();
+        layer.set_name(Self::FILTER_NODE);
+        layer.set_layer(Self::FILTER_LAYER);
+        layer.add_child(&rect);
+        layer
+    }
+}
+
+/// Daltonizes the screen: simulates the deficiency in LMS space and shifts the colors that would be lost into channels that can still be told apart
+const COLORBLIND_SHADER: &str = r#"
+shader_type canvas_item;
+
+uniform sampler2D screen_texture : hint_screen_texture, filter_nearest;
+uniform int mode = 0;
+
+const mat3 RGB_TO_LMS = mat3(
+	vec3(17.8824, 3.45565, 0.0299566),
+	vec3(43.5161, 27.1554, 0.184309),
+	vec3(4.11935, 3.86714, 1.46709));
+const mat3 LMS_TO_RGB = mat3(
+	vec3(0.0809444479, -0.0102485335, -0.000365296938),
+	vec3(-0.130504409, 0.0540193266, -0.00412161469),
+	vec3(0.116721066, -0.113614708, 0.693511405));
+
+void fragment() {
+	vec3 color = texture(screen_texture, SCREEN_UV).rgb;
+	vec3 lms = RGB_TO_LMS * color;
+	vec3 sim = lms;
+	if (mode == 1) {
+		sim.x = 2.02344 * lms.y - 2.52581 * lms.z;
+	} else if (mode == 2) {
+		sim.y = 0.494207 * lms.x + 1.24827 * lms.z;
+	} else if (mode == 3) {
+		sim.z = -0.395913 * lms.x + 0.801109 * lms.y;
+	}
+	vec3 error = color - LMS_TO_RGB * sim;
+	vec3 shift = vec3(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+	COLOR = vec4(clamp(color + shift, 0.0, 1.0), 1.0);
+}
+"#;
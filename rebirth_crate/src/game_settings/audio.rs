@@ -0,0 +1,100 @@
+use godot::classes::AudioServer;
+use godot::global::linear_to_db;
+use godot::prelude::*;
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, tool)]
+pub struct AudioSettings {
+    base: Base<Resource>,
+    //
+    //  Volumes
+    //
+    #[export(range = (0.0, 1.0))]
+    master_volume: f32,
+    #[export(range = (0.0, 1.0))]
+    music_volume: f32,
+    #[export(range = (0.0, 1.0))]
+    sfx_volume: f32,
+    #[export(range = (0.0, 1.0))]
+    voice_volume: f32,
+    #[export(range = (0.0, 1.0))]
+    ambience_volume: f32,
+    //
+    //  Mutes
+    //
+    #[export]
+    master_muted: bool,
+    #[export]
+    music_muted: bool,
+    #[export]
+    sfx_muted: bool,
+    #[export]
+    voice_muted: bool,
+    #[export]
+    ambience_muted: bool,
+    //
+    //  Device
+    //
+    #[export]
+    /// One of `AudioServer.get_output_device_list()`. Falls back to the system default when the device is missing
+    output_device: GString,
+}
+
+#[godot_api]
+impl IResource for AudioSettings {
+    fn init(base: Base<Self::Base>) -> Self {
+        Self {
+            base,
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 1.0,
+            voice_volume: 1.0,
+            ambience_volume: 0.8,
+            master_muted: false,
+            music_muted: false,
+            sfx_muted: false,
+            voice_muted: false,
+            ambience_muted: false,
+            output_device: AudioSettings::DEFAULT_DEVICE.into(),
+        }
+    }
+}
+
+#[godot_api]
+impl AudioSettings {
+    const DEFAULT_DEVICE: &'static str = "Default";
+
+    #[func]
+    /// Sets the volume and mute state of each bus. Buses missing from the project's bus layout are skipped
+    pub fn apply(&self) {
+        let mut server = AudioServer::singleton();
+        let buses = [
+            ("Master", self.master_volume, self.master_muted),
+            ("Music", self.music_volume, self.music_muted),
+            ("SFX", self.sfx_volume, self.sfx_muted),
+            ("Voice", self.voice_volume, self.voice_muted),
+            ("Ambience", self.ambience_volume, self.ambience_muted),
+        ];
+        for (bus, volume, muted) in buses {
+            let index = server.get_bus_index(bus);
+            if index < 0 {
+                continue;
+            }
+            server.set_bus_volume_db(index, linear_to_db(volume as f64) as f32);
+            server.set_bus_mute(index, muted);
+        }
+
+        let device = if server
+            .get_output_device_list()
+            .as_slice()
+            .contains(&self.output_device)
+        {
+            self.output_device.clone()
+        } else {
+            Self::DEFAULT_DEVICE.into()
+        };
+        if server.get_output_device() != device {
+            server.set_output_device(&device);
+        }
+    }
+}
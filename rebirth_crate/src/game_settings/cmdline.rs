@@ -0,0 +1,36 @@
+use godot::prelude::*;
+
+/// Short names accepted in place of a category name
+const CATEGORY_ALIASES: &[(&str, &str)] = &[("gfx", "graphics"), ("a11y", "accessibility")];
+
+/// A settings override from the command line, e.g. `--gfx.msaa=0`
+#[derive(Debug)]
+pub struct CmdlineOverride {
+    pub category: String,
+    pub key: String,
+    pub value: String,
+}
+
+/// Finds every `--category.key=value` argument. A flag without a value, e.g. `--gfx.ssr_enabled`, means `true`
+///
+/// Arguments without a dot in their name are left alone since they belong to something else
+pub fn parse(args: &PackedStringArray) -> Vec<CmdlineOverride> {
+    args.as_slice()
+        .iter()
+        .filter_map(|arg| {
+            let arg = arg.to_string();
+            let arg = arg.strip_prefix("--")?;
+            let (path, value) = arg.split_once('=').unwrap_or((arg, "true"));
+            let (category, key) = path.split_once('.')?;
+            let category = CATEGORY_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == category)
+                .map_or(category, |(_, name)| name);
+            Some(CmdlineOverride {
+                category: category.to_string(),
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
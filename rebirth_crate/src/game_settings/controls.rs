@@ -0,0 +1,102 @@
+use godot::classes::InputMap;
+use godot::prelude::*;
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, tool)]
+/// How look input is turned into camera movement. Controllers read their look input through `get_mouse_look` and `get_gamepad_look` instead of scaling it themselves
+pub struct ControlsSettings {
+    base: Base<Resource>,
+
+    #[export(range = (0.05, 10.0))]
+    mouse_sensitivity: f32,
+    #[export(range = (0.5, 30.0))]
+    gamepad_sensitivity: f32,
+    #[export]
+    invert_x: bool,
+    #[export]
+    invert_y: bool,
+    #[export(range = (0.0, 0.9))]
+    /// Stick deflection below this is ignored. Applied to the `look_*` actions
+    gamepad_deadzone: f32,
+    #[export(range = (0.0, 4.0))]
+    /// How much faster looking gets while the stick is held past `gamepad_acceleration_threshold`, as extra sensitivity multiples. 0 turns acceleration off
+    gamepad_acceleration: f32,
+    #[export(range = (0.1, 1.0))]
+    /// The stick deflection past which acceleration builds up
+    gamepad_acceleration_threshold: f32,
+    #[export(range = (0.0, 5.0))]
+    /// Seconds the stick has to be held past the threshold to reach full acceleration
+    gamepad_acceleration_time: f32,
+}
+
+#[godot_api]
+impl IResource for ControlsSettings {
+    fn init(base: Base<Self::Base>) -> Self {
+        Self {
+            base,
+            mouse_sensitivity: 1.0,
+            gamepad_sensitivity: 10.0,
+            invert_x: false,
+            invert_y: false,
+            gamepad_deadzone: 0.2,
+            gamepad_acceleration: 1.0,
+            gamepad_acceleration_threshold: 0.9,
+            gamepad_acceleration_time: 0.5,
+        }
+    }
+}
+
+#[godot_api]
+impl ControlsSettings {
+    const LOOK_ACTIONS: [&'static str; 4] = ["look_left", "look_right", "look_up", "look_down"];
+
+    #[func]
+    /// Sets the deadzone of the `look_*` actions. Must run after [InputSettings](super::input::InputSettings) since that reloads the input map
+    pub fn apply(&self) {
+        let mut input_map = InputMap::singleton();
+        for action in Self::LOOK_ACTIONS {
+            if input_map.has_action(action) {
+                input_map.action_set_deadzone(action, self.gamepad_deadzone);
+            }
+        }
+    }
+
+    #[func]
+    /// Scales a mouse motion by the mouse sensitivity and inversion settings
+    pub fn get_mouse_look(&self, relative: Vector2) -> Vector2 {
+        self.invert(relative * self.mouse_sensitivity)
+    }
+
+    #[func]
+    /// Whether a look stick vector is deflected far enough to build up acceleration. Controllers add up how long this holds and pass it to `get_gamepad_look`
+    pub fn is_gamepad_accelerating(&self, stick: Vector2) -> bool {
+        stick.length() >= self.gamepad_acceleration_threshold
+    }
+
+    #[func]
+    /// Scales a look stick vector, e.g. from `Input.get_vector`, by the gamepad sensitivity, acceleration and inversion settings
+    ///
+    /// `accelerating_for` is how many seconds the stick has been held past the threshold, see `is_gamepad_accelerating`
+    pub fn get_gamepad_look(&self, stick: Vector2, accelerating_for: f32) -> Vector2 {
+        let deflection = stick.length().min(1.0);
+        if deflection <= f32::EPSILON {
+            return Vector2::ZERO;
+        }
+        let ramp = if self.gamepad_acceleration_time > 0.0 {
+            (accelerating_for / self.gamepad_acceleration_time).clamp(0.0, 1.0)
+        } else if self.is_gamepad_accelerating(stick) {
+            1.0
+        } else {
+            0.0
+        };
+        let acceleration = 1.0 + self.gamepad_acceleration.max(0.0) * ramp;
+        self.invert(stick.normalized() * deflection * self.gamepad_sensitivity * acceleration)
+    }
+
+    fn invert(&self, look: Vector2) -> Vector2 {
+        Vector2::new(
+            if self.invert_x { -look.x } else { look.x },
+            if self.invert_y { -look.y } else { look.y },
+        )
+    }
+}
@@ -1,13 +1,88 @@
-use godot::classes::viewport::{Msaa, ScreenSpaceAa};
-use godot::classes::{CameraAttributes, Environment, Viewport};
+use godot::classes::display_server::{VSyncMode, WindowMode};
+use godot::classes::rendering_server::ShadowQuality as SoftShadowQuality;
+use godot::classes::viewport::{Msaa, Scaling3DMode, ScreenSpaceAa};
+use godot::classes::{
+    Camera3D, CameraAttributes, CameraAttributesPractical, DisplayServer, Engine, Environment,
+    RenderingServer, SubViewportContainer, Viewport, World3D, WorldEnvironment,
+};
 use godot::prelude::*;
 
 use crate::engine_helpers::engine;
 
+use super::presets::GraphicsPreset;
+
+#[derive(GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[godot(via=i32)]
+/// Shadow atlas resolution and soft shadow filtering
+pub enum ShadowQuality {
+    Low,
+    Medium,
+    #[default]
+    High,
+    Ultra,
+}
+
+impl ShadowQuality {
+    fn atlas_size(self) -> i32 {
+        match self {
+            ShadowQuality::Low => 1024,
+            ShadowQuality::Medium => 2048,
+            ShadowQuality::High => 4096,
+            ShadowQuality::Ultra => 8192,
+        }
+    }
+
+    fn soft_filter(self) -> SoftShadowQuality {
+        match self {
+            ShadowQuality::Low => SoftShadowQuality::HARD,
+            ShadowQuality::Medium => SoftShadowQuality::SOFT_LOW,
+            ShadowQuality::High => SoftShadowQuality::SOFT_MEDIUM,
+            ShadowQuality::Ultra => SoftShadowQuality::SOFT_HIGH,
+        }
+    }
+}
+
 #[derive(Debug, GodotClass)]
 #[class(base=Resource, tool)]
 pub struct GraphicsSettings {
     base: Base<Resource>,
+    #[export]
+    /// The preset the quality values below were last set from. Changing it in the inspector does not change the values, use `apply_preset`
+    preset: GraphicsPreset,
+    //
+    //  Display config
+    //
+    #[export(enum =
+        (   Windowed=WindowMode::WINDOWED.ord().into(),
+            Fullscreen=WindowMode::FULLSCREEN.ord().into(),
+            ExclusiveFullscreen=WindowMode::EXCLUSIVE_FULLSCREEN.ord().into()
+        ))]
+    window_mode: i32,
+    #[export]
+    /// The window size when windowed
+    resolution: Vector2i,
+    #[export(enum =
+        (   Disabled=VSyncMode::DISABLED.ord().into(),
+            Enabled=VSyncMode::ENABLED.ord().into(),
+            Adaptive=VSyncMode::ADAPTIVE.ord().into(),
+            Mailbox=VSyncMode::MAILBOX.ord().into()
+        ))]
+    vsync_mode: i32,
+    #[export(range = (0.0, 360.0))]
+    /// 0 is unlimited
+    max_fps: i32,
+    #[export(enum =
+        (   Bilinear=Scaling3DMode::BILINEAR.ord().into(),
+            Fsr=Scaling3DMode::FSR.ord().into(),
+            Fsr2=Scaling3DMode::FSR2.ord().into()
+        ))]
+    scaling_mode: i32,
+    #[export(range = (0.25, 2.0))]
+    /// The resolution 3D is rendered at relative to the viewport
+    render_scale: f32,
+    #[export(range = (1.0, 8.0))]
+    /// How many screen pixels each pixel of the game's low resolution viewport covers. Applied to every `SubViewportContainer` in the `pixelation` group
+    pixelation: i32,
     //
     //  Viewport config
     //
@@ -30,6 +105,8 @@ pub struct GraphicsSettings {
 
     #[export]
     mesh_lod_pixels: f32,
+    #[export]
+    shadow_quality: ShadowQuality,
 
     //
     //  Camera config
@@ -68,6 +145,15 @@ impl IResource for GraphicsSettings {
             msaa: Msaa::MSAA_2X.ord().into(),
             mesh_lod_pixels: 32.0,
             base,
+            preset: GraphicsPreset::Custom,
+            window_mode: WindowMode::WINDOWED.ord(),
+            resolution: Vector2i::new(1152, 648),
+            vsync_mode: VSyncMode::ENABLED.ord(),
+            max_fps: 0,
+            scaling_mode: Scaling3DMode::BILINEAR.ord(),
+            render_scale: 1.0,
+            pixelation: 3,
+            shadow_quality: ShadowQuality::High,
             use_taa: true,
             use_fxaa: false,
             use_occlusion_culling: true,
@@ -87,14 +173,116 @@ impl IResource for GraphicsSettings {
     }
 }
 
+impl GraphicsSettings {
+    pub fn preset(&self) -> GraphicsPreset {
+        self.preset
+    }
+}
+
 #[godot_api]
 impl GraphicsSettings {
     #[func]
-    pub fn apply(&self) {
-        let Some((mut viewport, mut env, mut cam)) = get_graphics_targets() else {
+    /// Sets every quality value to the preset's. Exposure is artistic and left alone, and [GraphicsPreset::Custom] only records the choice
+    pub fn apply_preset(&mut self, preset: GraphicsPreset) {
+        self.preset = preset;
+        if preset == GraphicsPreset::Custom {
             return;
+        }
+        let at_least = |minimum: GraphicsPreset| preset >= minimum;
+        self.msaa = match preset {
+            GraphicsPreset::Ultra => Msaa::MSAA_4X,
+            GraphicsPreset::Medium | GraphicsPreset::High => Msaa::MSAA_2X,
+            _ => Msaa::DISABLED,
+        }
+        .ord();
+        self.use_taa = at_least(GraphicsPreset::High);
+        self.use_fxaa = !at_least(GraphicsPreset::Medium);
+        self.use_occlusion_culling = at_least(GraphicsPreset::Low);
+        self.use_debanding = at_least(GraphicsPreset::Medium);
+        self.mesh_lod_pixels = match preset {
+            GraphicsPreset::Potato => 64.0,
+            GraphicsPreset::Low => 48.0,
+            GraphicsPreset::Medium => 32.0,
+            GraphicsPreset::High => 16.0,
+            _ => 8.0,
+        };
+        self.shadow_quality = match preset {
+            GraphicsPreset::Potato | GraphicsPreset::Low => ShadowQuality::Low,
+            GraphicsPreset::Medium => ShadowQuality::Medium,
+            GraphicsPreset::High => ShadowQuality::High,
+            _ => ShadowQuality::Ultra,
         };
-        // Viewport
+        self.fog_enabled = true;
+        self.glow_enabled = at_least(GraphicsPreset::Low);
+        self.sdfgi_enabled = at_least(GraphicsPreset::High);
+        self.ssr_enabled = at_least(GraphicsPreset::High);
+        self.ssao_enabled = at_least(GraphicsPreset::Medium);
+        self.ssil_enabled = at_least(GraphicsPreset::Ultra);
+        self.volumetric_fog_enabled = at_least(GraphicsPreset::High);
+    }
+
+    const PIXELATION_GROUP: &'static str = "pixelation";
+
+    #[constant]
+    /// Set in the result of `apply_to` when the viewport settings were applied
+    pub const APPLIED_VIEWPORT: i32 = 1;
+    #[constant]
+    /// Set in the result of `apply_to` when the environment settings were applied
+    pub const APPLIED_ENVIRONMENT: i32 = 2;
+    #[constant]
+    /// Set in the result of `apply_to` when the exposure settings were applied
+    pub const APPLIED_CAMERA_ATTRIBUTES: i32 = 4;
+
+    #[func]
+    /// Applies the display settings, then everything else to each viewport rendering 3D (including `SubViewport`s), its world and its current camera
+    pub fn apply(&self) {
+        self.apply_display();
+        for viewport in engine::get_3d_viewports() {
+            let camera = viewport.get_camera_3d();
+            self.apply_to(Some(viewport), None, camera, false);
+        }
+    }
+
+    #[func]
+    /// Applies the settings to whichever of the targets are given and returns the `APPLIED_*` flags of what was applied
+    ///
+    /// The environment is taken from `world_environment`, or else the viewport's world. The camera attributes are taken from `camera`, then `world_environment`, then the world. With `create_missing`, default resources are created where none are set instead of skipping that part
+    pub fn apply_to(
+        &self,
+        viewport: Option<Gd<Viewport>>,
+        world_environment: Option<Gd<WorldEnvironment>>,
+        camera: Option<Gd<Camera3D>>,
+        create_missing: bool,
+    ) -> i32 {
+        let mut applied = 0;
+        let world = viewport
+            .as_ref()
+            .and_then(|viewport| viewport.find_world_3d())
+            .or_else(|| camera.as_ref().and_then(|camera| camera.get_world_3d()));
+
+        if let Some(viewport) = viewport {
+            self.apply_viewport(viewport);
+            applied |= Self::APPLIED_VIEWPORT;
+        }
+        if let Some(env) =
+            find_environment(world_environment.clone(), world.clone(), create_missing)
+        {
+            self.apply_environment(env);
+            applied |= Self::APPLIED_ENVIRONMENT;
+        }
+        if let Some(attributes) =
+            find_camera_attributes(camera, world_environment, world, create_missing)
+        {
+            self.apply_camera_attributes(attributes, 1.0);
+            applied |= Self::APPLIED_CAMERA_ATTRIBUTES;
+        }
+        applied
+    }
+
+    fn apply_viewport(&self, mut viewport: Gd<Viewport>) {
+        viewport.set_scaling_3d_mode(Scaling3DMode::from_ord(self.scaling_mode));
+        viewport.set_scaling_3d_scale(self.render_scale);
+        viewport.set_positional_shadow_atlas_size(self.shadow_quality.atlas_size());
         viewport.set_msaa_3d(Msaa::from_ord(self.msaa));
         viewport.set_mesh_lod_threshold(self.mesh_lod_pixels);
         viewport.set_use_taa(self.use_taa);
@@ -104,17 +292,20 @@ impl GraphicsSettings {
             true => ScreenSpaceAa::FXAA,
             false => ScreenSpaceAa::DISABLED,
         });
+    }
 
-        // Camera
+    /// Sets the exposure settings, multiplying the exposure by `exposure_scale`. See [GraphicsOverrideVolume](super::override_volume::GraphicsOverrideVolume)
+    pub fn apply_camera_attributes(&self, mut cam: Gd<CameraAttributes>, exposure_scale: f32) {
         cam.set_auto_exposure_enabled(self.use_dynamic_exposure);
         if self.use_dynamic_exposure {
-            cam.set_exposure_multiplier(self.static_exposure);
-        } else {
-            cam.set_auto_exposure_scale(self.dynamic_exposure);
+            cam.set_auto_exposure_scale(self.dynamic_exposure * exposure_scale);
             cam.set_auto_exposure_speed(self.dynamic_exposure_speed);
+        } else {
+            cam.set_exposure_multiplier(self.static_exposure * exposure_scale);
         }
+    }
 
-        // Environment
+    fn apply_environment(&self, mut env: Gd<Environment>) {
         env.set_fog_enabled(self.fog_enabled);
         env.set_glow_enabled(self.glow_enabled);
         env.set_sdfgi_enabled(self.sdfgi_enabled);
@@ -123,20 +314,104 @@ impl GraphicsSettings {
         env.set_ssil_enabled(self.ssil_enabled);
         env.set_volumetric_fog_enabled(self.volumetric_fog_enabled);
     }
+
+    /// Window, frame pacing, shadow and pixelation settings. None of these depend on the world, and the window ones are skipped in the editor so they don't resize it
+    fn apply_display(&self) {
+        let mut rendering = RenderingServer::singleton();
+        let atlas_size = self.shadow_quality.atlas_size();
+        rendering.directional_shadow_atlas_set_size(atlas_size, true);
+        rendering.directional_soft_shadow_filter_set_quality(self.shadow_quality.soft_filter());
+        rendering.positional_soft_shadow_filter_set_quality(self.shadow_quality.soft_filter());
+
+        if let Some(mut tree) = engine::get_scene_tree() {
+            for node in tree
+                .get_nodes_in_group(Self::PIXELATION_GROUP)
+                .iter_shared()
+            {
+                if let Ok(mut container) = node.try_cast::<SubViewportContainer>() {
+                    container.set_stretch_shrink(self.pixelation.max(1));
+                }
+            }
+        }
+
+        if Engine::singleton().is_editor_hint() {
+            return;
+        }
+        Engine::singleton().set_max_fps(self.max_fps.max(0));
+        let mut display = DisplayServer::singleton();
+        display.window_set_vsync_mode(VSyncMode::from_ord(self.vsync_mode));
+        let mode = WindowMode::from_ord(self.window_mode);
+        if display.window_get_mode() != mode {
+            display.window_set_mode(mode);
+        }
+        if mode == WindowMode::WINDOWED && display.window_get_size() != self.resolution {
+            display.window_set_size(self.resolution);
+            let screen = display.window_get_current_screen();
+            let origin = display.screen_get_position_ex().screen(screen).done();
+            let size = display.screen_get_size_ex().screen(screen).done();
+            display.window_set_position(origin + (size - self.resolution) / 2);
+        }
+    }
 }
 
-fn get_graphics_targets() -> Option<(Gd<Viewport>, Gd<Environment>, Gd<CameraAttributes>)> {
-    let Some(viewport) = engine::get_viewport() else {
-        return None;
-    };
-    let Some(world) = viewport.get_world_3d() else {
-        return None;
-    };
-    let Some(cam) = world.get_camera_attributes() else {
-        return None;
-    };
-    let Some(env) = world.get_environment() else {
+/// The environment of the `WorldEnvironment` if given, or else of the world
+fn find_environment(
+    world_environment: Option<Gd<WorldEnvironment>>,
+    world: Option<Gd<World3D>>,
+    create_missing: bool,
+) -> Option<Gd<Environment>> {
+    if let Some(mut world_environment) = world_environment {
+        if let Some(env) = world_environment.get_environment() {
+            return Some(env);
+        }
+        if create_missing {
+            let env = Environment::new_gd();
+            world_environment.set_environment(&env);
+            return Some(env);
+        }
+    }
+    let mut world = world?;
+    if let Some(env) = world.get_environment() {
+        return Some(env);
+    }
+    if !create_missing {
         return None;
-    };
-    return Some((viewport, env, cam));
+    }
+    let env = Environment::new_gd();
+    world.set_environment(&env);
+    Some(env)
+}
+
+/// The first camera attributes set on the camera, `WorldEnvironment` or world. Missing attributes are created on the most specific of those that was given
+pub fn find_camera_attributes(
+    camera: Option<Gd<Camera3D>>,
+    world_environment: Option<Gd<WorldEnvironment>>,
+    world: Option<Gd<World3D>>,
+    create_missing: bool,
+) -> Option<Gd<CameraAttributes>> {
+    let existing = camera
+        .as_ref()
+        .and_then(|camera| camera.get_attributes())
+        .or_else(|| {
+            world_environment
+                .as_ref()
+                .and_then(|world_environment| world_environment.get_camera_attributes())
+        })
+        .or_else(|| {
+            world
+                .as_ref()
+                .and_then(|world| world.get_camera_attributes())
+        });
+    if existing.is_some() || !create_missing {
+        return existing;
+    }
+    let attributes = CameraAttributesPractical::new_gd().upcast::<CameraAttributes>();
+    if let Some(mut camera) = camera {
+        camera.set_attributes(&attributes);
+    } else if let Some(mut world_environment) = world_environment {
+        world_environment.set_camera_attributes(&attributes);
+    } else {
+        world?.set_camera_attributes(&attributes);
+    }
+    Some(attributes)
 }
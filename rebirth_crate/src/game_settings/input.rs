@@ -0,0 +1,258 @@
+use godot::classes::{
+    InputEvent, InputEventJoypadButton, InputEventJoypadMotion, InputEventKey,
+    InputEventMouseButton, InputMap, Os,
+};
+use godot::global::{JoyAxis, JoyButton, Key, MouseButton};
+use godot::prelude::*;
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, tool)]
+/// Per-action input bindings layered on top of the project's input map
+///
+/// Bindings are stored as short codes so they read well in the user settings file:
+/// - `key:W` a physical key, by its name
+/// - `mouse:1` a mouse button, by index
+/// - `joy_button:0` a gamepad button, by index
+/// - `joy_axis:2:-` one direction of a gamepad axis
+pub struct InputSettings {
+    base: Base<Resource>,
+
+    #[export]
+    /// Action name to the binding codes that replace its events. Actions missing here keep the bindings from the project's input map
+    bindings: Dictionary,
+}
+
+#[godot_api]
+impl IResource for InputSettings {
+    fn init(base: Base<Self::Base>) -> Self {
+        Self {
+            base,
+            bindings: dict! {
+                "look_left": PackedStringArray::from(&["joy_axis:2:-".into()]),
+                "look_right": PackedStringArray::from(&["joy_axis:2:+".into()]),
+                "look_up": PackedStringArray::from(&["joy_axis:3:-".into()]),
+                "look_down": PackedStringArray::from(&["joy_axis:3:+".into()]),
+            },
+        }
+    }
+}
+
+#[godot_api]
+impl InputSettings {
+    const DEFAULT_DEADZONE: f32 = 0.5;
+
+    #[func]
+    /// Restores the project's input map and replaces the events of every rebound action
+    pub fn apply(&self) {
+        let mut input_map = InputMap::singleton();
+        input_map.load_from_project_settings();
+        for (action, codes) in self.bindings.iter_shared() {
+            let Ok(action) = action.try_to::<StringName>() else {
+                continue;
+            };
+            if !input_map.has_action(&action) {
+                input_map
+                    .add_action_ex(&action)
+                    .deadzone(Self::DEFAULT_DEADZONE)
+                    .done();
+            }
+            input_map.action_erase_events(&action);
+            for code in codes
+                .try_to::<PackedStringArray>()
+                .unwrap_or_default()
+                .as_slice()
+            {
+                match binding_to_event(&code.to_string()) {
+                    Some(event) => input_map.action_add_event(&action, &event),
+                    None => godot_warn!("Ignoring unknown binding {} for {}", code, action),
+                }
+            }
+        }
+    }
+
+    #[func]
+    /// Every action that can be rebound, skipping godot's built in `ui_*` actions
+    pub fn get_actions(&self) -> Array<StringName> {
+        InputMap::singleton()
+            .get_actions()
+            .iter_shared()
+            .filter(|action| !action.to_string().starts_with("ui_"))
+            .collect()
+    }
+
+    #[func]
+    /// The binding codes currently used by an action, falling back to the project's input map when it hasn't been rebound
+    pub fn get_action_bindings(&self, action: StringName) -> PackedStringArray {
+        if let Some(codes) = self.bindings.get(action.clone()) {
+            return codes.try_to().unwrap_or_default();
+        }
+        let mut input_map = InputMap::singleton();
+        if !input_map.has_action(&action) {
+            return PackedStringArray::new();
+        }
+        input_map
+            .action_get_events(&action)
+            .iter_shared()
+            .filter_map(|event| event_to_binding(&event))
+            .map(GString::from)
+            .collect()
+    }
+
+    #[func]
+    /// The code an input event would be stored as, or an empty string if that kind of event can't be bound
+    pub fn get_binding_code(&self, event: Gd<InputEvent>) -> GString {
+        event_to_binding(&event).unwrap_or_default().into()
+    }
+
+    #[func]
+    /// Every other action already using the given event
+    pub fn get_conflicts(&self, action: StringName, event: Gd<InputEvent>) -> Array<StringName> {
+        let Some(code) = event_to_binding(&event) else {
+            return Array::new();
+        };
+        let code = GString::from(code);
+        self.get_actions()
+            .iter_shared()
+            .filter(|other| {
+                *other != action && self.get_action_bindings(other.clone()).contains(&code)
+            })
+            .collect()
+    }
+
+    #[func]
+    /// Binds the event to the action, replacing the action's previous binding from the same device (keyboard and mouse, or gamepad)
+    ///
+    /// Refuses and returns false if another action already uses the event, see `get_conflicts`
+    pub fn rebind(&mut self, action: StringName, event: Gd<InputEvent>) -> bool {
+        let Some(code) = event_to_binding(&event) else {
+            return false;
+        };
+        if !self.get_conflicts(action.clone(), event).is_empty() {
+            return false;
+        }
+        let gamepad = is_gamepad_binding(&code);
+        let mut codes: Vec<GString> = self
+            .get_action_bindings(action.clone())
+            .as_slice()
+            .iter()
+            .filter(|existing| is_gamepad_binding(&existing.to_string()) != gamepad)
+            .cloned()
+            .collect();
+        codes.push(code.into());
+        self.set_action_bindings(action, codes.into_iter().collect());
+        true
+    }
+
+    #[func]
+    /// Removes a single binding from an action
+    pub fn unbind(&mut self, action: StringName, code: GString) {
+        let codes = self
+            .get_action_bindings(action.clone())
+            .as_slice()
+            .iter()
+            .filter(|existing| **existing != code)
+            .cloned()
+            .collect();
+        self.set_action_bindings(action, codes);
+    }
+
+    #[func]
+    /// Forgets the player's bindings for an action so it uses the project's input map again
+    pub fn reset_action(&mut self, action: StringName) {
+        let mut bindings = self.bindings.duplicate_shallow();
+        bindings.remove(action);
+        self.bindings = bindings;
+        self.base_mut().emit_changed();
+    }
+
+    /// Dictionaries are shared between duplicated resources, so edits replace the dictionary instead of changing it in place. Otherwise rebinding would also change the project defaults it is compared against
+    fn set_action_bindings(&mut self, action: StringName, codes: PackedStringArray) {
+        let mut bindings = self.bindings.duplicate_shallow();
+        bindings.set(action, codes);
+        self.bindings = bindings;
+        self.base_mut().emit_changed();
+    }
+}
+
+fn is_gamepad_binding(code: &str) -> bool {
+    code.starts_with("joy_")
+}
+
+/// Converts an input event to its binding code. See [InputSettings]
+fn event_to_binding(event: &Gd<InputEvent>) -> Option<String> {
+    if let Ok(key) = event.clone().try_cast::<InputEventKey>() {
+        let keycode = match key.get_physical_keycode() {
+            Key::NONE => key.get_keycode(),
+            physical => physical,
+        };
+        if keycode == Key::NONE {
+            return None;
+        }
+        return Some(format!(
+            "key:{}",
+            Os::singleton().get_keycode_string(keycode)
+        ));
+    }
+    if let Ok(mouse) = event.clone().try_cast::<InputEventMouseButton>() {
+        return Some(format!("mouse:{}", mouse.get_button_index().ord()));
+    }
+    if let Ok(button) = event.clone().try_cast::<InputEventJoypadButton>() {
+        return Some(format!("joy_button:{}", button.get_button_index().ord()));
+    }
+    if let Ok(motion) = event.clone().try_cast::<InputEventJoypadMotion>() {
+        let direction = if motion.get_axis_value() < 0.0 {
+            "-"
+        } else {
+            "+"
+        };
+        return Some(format!(
+            "joy_axis:{}:{}",
+            motion.get_axis().ord(),
+            direction
+        ));
+    }
+    None
+}
+
+/// The device id of events that match every device, like those in the project's input map
+const ALL_DEVICES: i32 = -1;
+
+/// Creates the input event a binding code describes, matching input from every device. See [InputSettings]
+fn binding_to_event(code: &str) -> Option<Gd<InputEvent>> {
+    let mut parts = code.split(':');
+    let kind = parts.next()?;
+    let value = parts.next()?;
+    let mut event: Gd<InputEvent> = match kind {
+        "key" => {
+            let keycode = Os::singleton().find_keycode_from_string(value);
+            if keycode == Key::NONE {
+                return None;
+            }
+            let mut event = InputEventKey::new_gd();
+            event.set_physical_keycode(keycode);
+            event.upcast()
+        }
+        "mouse" => {
+            let mut event = InputEventMouseButton::new_gd();
+            event.set_button_index(MouseButton::try_from_ord(value.parse().ok()?)?);
+            event.upcast()
+        }
+        "joy_button" => {
+            let mut event = InputEventJoypadButton::new_gd();
+            event.set_button_index(JoyButton::try_from_ord(value.parse().ok()?)?);
+            event.upcast()
+        }
+        "joy_axis" => {
+            let mut event = InputEventJoypadMotion::new_gd();
+            event.set_axis(JoyAxis::try_from_ord(value.parse().ok()?)?);
+            event.set_axis_value(match parts.next()? {
+                "-" => -1.0,
+                _ => 1.0,
+            });
+            event.upcast()
+        }
+        _ => return None,
+    };
+    event.set_device(ALL_DEVICES);
+    Some(event)
+}
@@ -0,0 +1,448 @@
+use godot::classes::{
+    box_container::AlignmentMode,
+    control::{LayoutPreset, SizeFlags},
+    Button, CheckBox, ConfirmationDialog, Control, GridContainer, HBoxContainer, HSlider, IControl,
+    Label, LineEdit, OptionButton, Range, ScrollContainer, SpinBox, TabContainer, VBoxContainer,
+};
+use godot::global::PropertyHint;
+use godot::prelude::*;
+
+use crate::engine_helpers::CrateSingleton;
+
+use super::{overrides, presets::GraphicsPreset, GameSettings};
+
+#[derive(Debug, GodotClass)]
+#[class(base=Control, init)]
+/// A settings screen built from the exported properties of every [GameSettings] category
+///
+/// Booleans become checkboxes, enums option buttons, ranged numbers sliders and other numbers spin boxes. Settings of any other type are left out. Edits change the settings right away but only take effect once applied, and revert throws away everything since the last apply
+pub struct SettingsMenu {
+    #[export]
+    #[init(val = 15.0)]
+    /// Seconds the player has to confirm display changes before they are rolled back
+    confirm_seconds: f64,
+
+    /// Copies of every category as they were when last applied
+    snapshot: Vec<(GString, Gd<Resource>)>,
+    /// Seconds left before unconfirmed display changes are rolled back
+    confirm_remaining: Option<f64>,
+    tabs: Option<Gd<TabContainer>>,
+    confirm_dialog: Option<Gd<ConfirmationDialog>>,
+    base: Base<Control>,
+}
+
+#[godot_api]
+impl IControl for SettingsMenu {
+    fn ready(&mut self) {
+        let this = self.to_gd();
+
+        let mut tabs = TabContainer::new_alloc();
+        tabs.set_v_size_flags(SizeFlags::EXPAND_FILL);
+
+        let mut buttons = HBoxContainer::new_alloc();
+        buttons.set_alignment(AlignmentMode::END);
+        for (text, method) in [
+            ("Revert", "on_revert"),
+            ("Defaults", "on_defaults"),
+            ("Apply", "on_apply"),
+        ] {
+            let mut button = Button::new_alloc();
+            button.set_text(text);
+            button.connect("pressed", &Callable::from_object_method(&this, method));
+            buttons.add_child(&button);
+        }
+
+        let mut layout = VBoxContainer::new_alloc();
+        layout.set_anchors_and_offsets_preset(LayoutPreset::FULL_RECT);
+        layout.add_child(&tabs);
+        layout.add_child(&buttons);
+
+        let mut dialog = ConfirmationDialog::new_alloc();
+        dialog.set_title("Display settings");
+        dialog.connect(
+            "confirmed",
+            &Callable::from_object_method(&this, "on_display_confirmed"),
+        );
+        dialog.connect(
+            "canceled",
+            &Callable::from_object_method(&this, "on_display_rollback"),
+        );
+
+        self.base_mut().add_child(&layout);
+        self.base_mut().add_child(&dialog);
+        self.base_mut().set_process(false);
+        self.tabs = Some(tabs);
+        self.confirm_dialog = Some(dialog);
+        self.take_snapshot();
+        self.rebuild();
+    }
+
+    fn process(&mut self, delta: f64) {
+        let Some(remaining) = self.confirm_remaining else {
+            return;
+        };
+        let remaining = remaining - delta;
+        if remaining <= 0.0 {
+            if let Some(mut dialog) = self.confirm_dialog.clone() {
+                dialog.hide();
+            }
+            self.on_display_rollback();
+            return;
+        }
+        self.confirm_remaining = Some(remaining);
+        self.update_confirm_text();
+    }
+}
+
+#[godot_api]
+impl SettingsMenu {
+    /// Graphics settings that can leave the screen unusable, so they have to be confirmed after applying
+    const DISPLAY_KEYS: [&'static str; 7] = [
+        "window_mode",
+        "resolution",
+        "vsync_mode",
+        "max_fps",
+        "scaling_mode",
+        "render_scale",
+        "pixelation",
+    ];
+
+    #[func]
+    /// Applies and saves the settings. Display changes are rolled back unless confirmed within `confirm_seconds`
+    fn on_apply(&mut self) {
+        let Some(mut settings) = GameSettings::singleton() else {
+            return;
+        };
+        let display_changed = self.display_changed();
+        settings.bind_mut().apply();
+        if !display_changed {
+            settings.bind().save();
+            self.take_snapshot();
+            return;
+        }
+        self.confirm_remaining = Some(self.confirm_seconds);
+        self.update_confirm_text();
+        self.base_mut().set_process(true);
+        if let Some(mut dialog) = self.confirm_dialog.clone() {
+            dialog.popup_centered();
+        }
+    }
+
+    #[func]
+    /// Throws away every edit since the last apply
+    fn on_revert(&mut self) {
+        let Some(settings) = GameSettings::singleton() else {
+            return;
+        };
+        for (category, snapshot) in &self.snapshot {
+            if let Some(mut current) = settings.bind().get_category(category.clone()) {
+                overrides::copy(snapshot, &mut current);
+            }
+        }
+        self.rebuild();
+    }
+
+    #[func]
+    /// Puts every setting back to the project defaults. Like any other edit this takes effect once applied
+    fn on_defaults(&mut self) {
+        let Some(mut settings) = GameSettings::singleton() else {
+            return;
+        };
+        settings.bind_mut().reset_to_defaults();
+        self.rebuild();
+    }
+
+    #[func]
+    fn on_display_confirmed(&mut self) {
+        self.stop_confirming();
+        if let Some(settings) = GameSettings::singleton() {
+            settings.bind().save();
+        }
+        self.take_snapshot();
+    }
+
+    #[func]
+    /// Restores the display settings from before the last apply, keeping every other change
+    fn on_display_rollback(&mut self) {
+        self.stop_confirming();
+        let Some(mut settings) = GameSettings::singleton() else {
+            return;
+        };
+        let graphics = GString::from(GameSettings::CATEGORY_GFX);
+        if let (Some(previous), Some(mut current)) = (
+            self.snapshot_of(&graphics),
+            settings.bind().get_category(graphics.clone()),
+        ) {
+            for key in Self::DISPLAY_KEYS {
+                current.set(key, &previous.get(key));
+            }
+        }
+        settings.bind_mut().apply();
+        settings.bind().save();
+        self.take_snapshot();
+        self.rebuild();
+    }
+
+    #[func]
+    fn on_setting_edited(&mut self, value: Variant, category: GString, key: StringName) {
+        Self::edit(category, key, value);
+    }
+
+    #[func]
+    fn on_option_selected(
+        &mut self,
+        index: i64,
+        button: Gd<OptionButton>,
+        category: GString,
+        key: StringName,
+    ) {
+        let id = button.get_item_id(index as i32);
+        let is_preset = category == GString::from(GameSettings::CATEGORY_GFX)
+            && key == StringName::from(GameSettings::PRESET_KEY);
+        if !is_preset {
+            Self::edit(category, key, id.to_variant());
+            return;
+        }
+        // Presets change many other settings, so they go through GameSettings and the controls are rebuilt
+        let (Some(mut settings), Ok(preset)) = (
+            GameSettings::singleton(),
+            id.to_variant().try_to::<GraphicsPreset>(),
+        ) else {
+            return;
+        };
+        settings.bind_mut().set_graphics_preset(preset, false);
+        self.rebuild();
+    }
+
+    #[func]
+    fn on_vector_axis_edited(&mut self, value: f64, axis: i64, category: GString, key: StringName) {
+        let Some(settings) = GameSettings::singleton() else {
+            return;
+        };
+        let Ok(mut vector) = settings
+            .bind()
+            .get_value(category.clone(), key.clone())
+            .try_to::<Vector2i>()
+        else {
+            return;
+        };
+        match axis {
+            0 => vector.x = value as i32,
+            _ => vector.y = value as i32,
+        }
+        Self::edit(category, key, vector.to_variant());
+    }
+
+    /// Sets a setting through [GameSettings]. Sliders and spin boxes always report floats, so those are rounded for integer settings
+    fn edit(category: GString, key: StringName, value: Variant) {
+        let Some(mut settings) = GameSettings::singleton() else {
+            return;
+        };
+        let current = settings.bind().get_value(category.clone(), key.clone());
+        let value = match (current.get_type(), value.get_type()) {
+            (VariantType::INT, VariantType::FLOAT) => {
+                (value.to::<f64>().round() as i64).to_variant()
+            }
+            _ => value,
+        };
+        settings.bind_mut().set_value(category, key, value);
+    }
+
+    fn take_snapshot(&mut self) {
+        let Some(settings) = GameSettings::singleton() else {
+            return;
+        };
+        let settings = settings.bind();
+        self.snapshot = settings
+            .get_categories()
+            .as_slice()
+            .iter()
+            .filter_map(|category| {
+                let snapshot = settings.get_category(category.clone())?.duplicate()?;
+                Some((category.clone(), snapshot))
+            })
+            .collect();
+    }
+
+    fn snapshot_of(&self, category: &GString) -> Option<Gd<Resource>> {
+        self.snapshot
+            .iter()
+            .find(|(name, _)| name == category)
+            .map(|(_, snapshot)| snapshot.clone())
+    }
+
+    fn display_changed(&self) -> bool {
+        let graphics = GString::from(GameSettings::CATEGORY_GFX);
+        let (Some(previous), Some(current)) = (
+            self.snapshot_of(&graphics),
+            GameSettings::singleton().and_then(|settings| settings.bind().get_category(graphics)),
+        ) else {
+            return false;
+        };
+        Self::DISPLAY_KEYS
+            .iter()
+            .any(|key| previous.get(*key) != current.get(*key))
+    }
+
+    fn stop_confirming(&mut self) {
+        self.confirm_remaining = None;
+        self.base_mut().set_process(false);
+    }
+
+    fn update_confirm_text(&mut self) {
+        let (Some(mut dialog), Some(remaining)) =
+            (self.confirm_dialog.clone(), self.confirm_remaining)
+        else {
+            return;
+        };
+        dialog.set_text(&format!(
+            "Keep these display settings?\nReverting in {} seconds",
+            remaining.ceil()
+        ));
+    }
+
+    /// Replaces every tab with controls showing the current settings
+    fn rebuild(&mut self) {
+        let (Some(mut tabs), Some(settings)) = (self.tabs.clone(), GameSettings::singleton())
+        else {
+            return;
+        };
+        let current_tab = tabs.get_current_tab();
+        for mut child in tabs.get_children().iter_shared() {
+            tabs.remove_child(&child);
+            child.queue_free();
+        }
+
+        let categories = settings.bind().get_categories();
+        for category in categories.as_slice() {
+            let Some(resource) = settings.bind().get_category(category.clone()) else {
+                continue;
+            };
+            let mut grid = GridContainer::new_alloc();
+            grid.set_columns(2);
+            grid.set_h_size_flags(SizeFlags::EXPAND_FILL);
+            for property in overrides::setting_properties(&resource) {
+                let Some((key, mut editor)) = self.make_editor(category, &resource, &property)
+                else {
+                    continue;
+                };
+                let mut label = Label::new_alloc();
+                label.set_text(&display_name(&key.to_string()));
+                editor.set_h_size_flags(SizeFlags::EXPAND_FILL);
+                grid.add_child(&label);
+                grid.add_child(&editor);
+            }
+            if grid.get_child_count() == 0 {
+                grid.free();
+                continue;
+            }
+            let mut scroll = ScrollContainer::new_alloc();
+            scroll.set_name(&display_name(&category.to_string()));
+            scroll.add_child(&grid);
+            tabs.add_child(&scroll);
+        }
+        if current_tab >= 0 && current_tab < tabs.get_tab_count() {
+            tabs.set_current_tab(current_tab);
+        }
+    }
+
+    /// Builds the control for a single setting, or nothing if its type isn't supported
+    fn make_editor(
+        &self,
+        category: &GString,
+        resource: &Gd<Resource>,
+        property: &Dictionary,
+    ) -> Option<(StringName, Gd<Control>)> {
+        let key = property.get("name")?.try_to::<StringName>().ok()?;
+        let hint = property
+            .get("hint")
+            .and_then(|hint| hint.try_to::<i32>().ok())
+            .unwrap_or_default();
+        let hint_string = property
+            .get("hint_string")
+            .map(|hint| hint.to_string())
+            .unwrap_or_default();
+        let value = resource.get(&key);
+        let this = self.to_gd();
+        let edited = Callable::from_object_method(&this, "on_setting_edited")
+            .bindv(&varray![category.clone(), key.clone()]);
+
+        let editor: Gd<Control> = match value.get_type() {
+            VariantType::BOOL => {
+                let mut check = CheckBox::new_alloc();
+                check.set_pressed_no_signal(value.to::<bool>());
+                check.connect("toggled", &edited);
+                check.upcast()
+            }
+            VariantType::INT if hint == PropertyHint::ENUM.ord() => {
+                let mut button = OptionButton::new_alloc();
+                for (label, id) in overrides::enum_items(&hint_string) {
+                    button.add_item_ex(&label).id(id).done();
+                }
+                let selected = button.get_item_index(value.to::<i32>());
+                button.select(selected);
+                let selected = Callable::from_object_method(&this, "on_option_selected")
+                    .bindv(&varray![button.clone(), category.clone(), key.clone()]);
+                button.connect("item_selected", &selected);
+                button.upcast()
+            }
+            VariantType::INT | VariantType::FLOAT => {
+                let is_int = value.get_type() == VariantType::INT;
+                let mut range: Gd<Range> = if hint == PropertyHint::RANGE.ord() {
+                    let mut slider = HSlider::new_alloc();
+                    let bounds: Vec<f64> = hint_string
+                        .split(',')
+                        .filter_map(|bound| bound.trim().parse().ok())
+                        .collect();
+                    if let [min, max, ..] = bounds[..] {
+                        slider.set_min(min);
+                        slider.set_max(max);
+                    }
+                    slider.set_custom_minimum_size(Vector2::new(160.0, 0.0));
+                    slider.upcast()
+                } else {
+                    let mut spin = SpinBox::new_alloc();
+                    spin.set_allow_greater(true);
+                    spin.set_allow_lesser(true);
+                    spin.upcast()
+                };
+                range.set_step(if is_int { 1.0 } else { 0.01 });
+                range.set_value_no_signal(value.to::<f64>());
+                range.connect("value_changed", &edited);
+                range.upcast()
+            }
+            VariantType::VECTOR2I => {
+                let vector = value.to::<Vector2i>();
+                let mut row = HBoxContainer::new_alloc();
+                for (axis, component) in [vector.x, vector.y].into_iter().enumerate() {
+                    let mut spin = SpinBox::new_alloc();
+                    spin.set_max(16384.0);
+                    spin.set_value_no_signal(component as f64);
+                    let edited_axis = Callable::from_object_method(&this, "on_vector_axis_edited")
+                        .bindv(&varray![axis as i64, category.clone(), key.clone()]);
+                    spin.connect("value_changed", &edited_axis);
+                    row.add_child(&spin);
+                }
+                row.upcast()
+            }
+            VariantType::STRING => {
+                let mut line = LineEdit::new_alloc();
+                line.set_text(&value.to::<GString>());
+                line.connect("text_changed", &edited);
+                line.upcast()
+            }
+            _ => return None,
+        };
+        Some((key, editor))
+    }
+}
+
+/// Turns a property name like `use_dynamic_exposure` into `Use dynamic exposure`
+fn display_name(name: &str) -> String {
+    let spaced = name.replace('_', " ");
+    let mut chars = spaced.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => spaced,
+    }
+}
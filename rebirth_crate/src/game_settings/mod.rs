@@ -1,54 +1,651 @@
+use accessibility::AccessibilitySettings;
+use audio::AudioSettings;
+use controls::ControlsSettings;
+use std::collections::BTreeMap;
+
 use godot::{
-    classes::{Engine, ProjectSettings},
+    classes::{
+        resource_loader::CacheMode, Engine, FileAccess, Os, ProjectSettings, ResourceLoader, Time,
+    },
+    global::{str_to_var, var_to_str},
     meta::AsArg,
     prelude::*,
 };
 use graphics::GraphicsSettings;
+use input::InputSettings;
+use overrides::SettingsOverrides;
+use presets::GraphicsPreset;
+
+use crate::engine_helpers::{engine, print_verbose, CrateSingleton};
 
+mod accessibility;
+mod audio;
+mod cmdline;
+mod controls;
 mod graphics;
+mod input;
+mod menu;
+mod override_volume;
+mod overrides;
+mod presets;
 
 pub fn register() {
-    Engine::singleton().register_singleton(GameSettings::ID, &GameSettings::new_alloc());
+    let settings = GameSettings::new_alloc();
+    Engine::singleton().register_singleton(GameSettings::ID, &settings);
+    engine::call_when_tree_exists(&Callable::from_object_method(&settings, "start"));
 }
 
 pub fn unregister() {
     Engine::singleton().unregister_singleton(GameSettings::ID);
 }
 
+/// Whether hold interactions are toggled by a press instead of held, from [AccessibilitySettings]. False before the settings are registered
+pub fn toggle_hold_actions() -> bool {
+    GameSettings::singleton().is_some_and(|settings| {
+        settings
+            .bind()
+            .accessibility
+            .bind()
+            .get_toggle_hold_actions()
+    })
+}
+
+/// A settings resource the player can change, alongside the project defaults it was duplicated from
+#[derive(Debug)]
+struct SettingsCategory {
+    name: &'static str,
+    defaults: Gd<Resource>,
+    current: Gd<Resource>,
+    /// When the project defaults were last written to disk, for hot reloading
+    modified_time: u64,
+}
+
 #[derive(Debug, GodotClass)]
 #[class(base=Object, tool)]
+/// Holds the project's settings resources and applies them to the engine
+///
+/// Each category starts from the resource configured in the project settings and has the player's changes from `user://settings.ron` layered on top. The project resources themselves are never modified
+///
+/// With `hot_reload` on, which it is by default in debug builds, the project resources are watched from startup. Saving one on disk reloads it and re-applies the category, so values can be tuned while the game runs
 struct GameSettings {
     base: Base<Object>,
 
+    #[var(get)]
     graphics: Gd<GraphicsSettings>,
+    #[var(get)]
+    audio: Gd<AudioSettings>,
+    #[var(get)]
+    input: Gd<InputSettings>,
+    #[var(get)]
+    controls: Gd<ControlsSettings>,
+    #[var(get)]
+    accessibility: Gd<AccessibilitySettings>,
+    categories: Vec<SettingsCategory>,
+    /// Settings overridden from the command line, by category and key, along with what the user settings file held for them so saving doesn't persist the override
+    cmdline_shadowed: BTreeMap<String, BTreeMap<String, Option<String>>>,
+    #[var]
+    /// Whether changes to the settings resources are re-applied automatically
+    hot_reload: bool,
+    is_watching: bool,
+    next_poll_msec: u64,
 }
 
 #[godot_api]
 impl IObject for GameSettings {
     fn init(base: Base<Object>) -> Self {
-        let gfx_path = get_setting_or_default(
+        let mut categories = Vec::new();
+        let graphics = load_category::<GraphicsSettings>(
+            &mut categories,
+            Self::CATEGORY_GFX,
             Self::SETTINGS_GFX,
-            "res://settings/graphics.tres".to_variant(),
+            "res://settings/graphics.tres",
+        );
+        let audio = load_category::<AudioSettings>(
+            &mut categories,
+            Self::CATEGORY_AUDIO,
+            Self::SETTINGS_AUDIO,
+            "res://settings/audio.tres",
+        );
+        let input = load_category::<InputSettings>(
+            &mut categories,
+            Self::CATEGORY_INPUT,
+            Self::SETTINGS_INPUT,
+            "res://settings/input.tres",
+        );
+        let controls = load_category::<ControlsSettings>(
+            &mut categories,
+            Self::CATEGORY_CONTROLS,
+            Self::SETTINGS_CONTROLS,
+            "res://settings/controls.tres",
+        );
+        let accessibility = load_category::<AccessibilitySettings>(
+            &mut categories,
+            Self::CATEGORY_ACCESSIBILITY,
+            Self::SETTINGS_ACCESSIBILITY,
+            "res://settings/accessibility.tres",
         );
 
-        let gfx =
-            try_load::<GraphicsSettings>(&GString::from_variant(&gfx_path)).unwrap_or_default();
-
-        Self {
+        let mut settings = Self {
             base,
-            graphics: gfx,
-        }
+            graphics,
+            audio,
+            input,
+            controls,
+            accessibility,
+            categories,
+            cmdline_shadowed: BTreeMap::new(),
+            hot_reload: Os::singleton().is_debug_build(),
+            is_watching: false,
+            next_poll_msec: 0,
+        };
+        let saved = settings.load_overrides();
+        settings.apply_cmdline_overrides(&saved, None);
+        settings
     }
 }
 
+impl CrateSingleton for GameSettings {
+    const ID: &'static str = "GameSettings";
+}
+
 #[godot_api]
 impl GameSettings {
-    pub const ID: &'static str = "GameSettings";
     const SETTINGS_GFX: &'static str = "GameSettings/GraphicsSettings";
+    const SETTINGS_AUDIO: &'static str = "GameSettings/AudioSettings";
+    const SETTINGS_INPUT: &'static str = "GameSettings/InputSettings";
+    const SETTINGS_CONTROLS: &'static str = "GameSettings/ControlsSettings";
+    const SETTINGS_ACCESSIBILITY: &'static str = "GameSettings/AccessibilitySettings";
+    const SETTINGS_USER_PATH: &'static str = "GameSettings/UserSettingsPath";
+    const CATEGORY_GFX: &'static str = "graphics";
+    const PRESET_KEY: &'static str = "preset";
+    const CATEGORY_AUDIO: &'static str = "audio";
+    const CATEGORY_INPUT: &'static str = "input";
+    const CATEGORY_CONTROLS: &'static str = "controls";
+    const CATEGORY_ACCESSIBILITY: &'static str = "accessibility";
+    const POLL_INTERVAL_MSEC: u64 = 1000;
+
+    #[signal]
+    /// Emitted when settings of a category are changed through [GameSettings], e.g. by `set_value` or `reset_to_defaults`
+    fn settings_changed(category: GString) {}
+
+    #[func]
+    /// Applies the loaded settings once the tree exists, so the player's saved settings are in effect from the first frame. Hot reloading starts watching the settings resources here too
+    fn start(&mut self) {
+        override_volume::add_system();
+        if Engine::singleton().is_editor_hint() {
+            return;
+        }
+        self.apply();
+        self.watch_resources();
+        let overridden = self
+            .cmdline_shadowed
+            .iter()
+            .flat_map(|(category, keys)| {
+                keys.keys().map(move |key| format!("{}.{}", category, key))
+            })
+            .collect::<Vec<_>>();
+        if !overridden.is_empty() {
+            print_verbose!(
+                "Applied settings from the command line: {}",
+                overridden.join(", ")
+            );
+        }
+    }
+
     #[func]
     fn apply(&mut self) {
         self.graphics.bind().apply();
+        self.audio.bind().apply();
+        self.input.bind().apply();
+        self.controls.bind().apply();
+        self.accessibility.bind_mut().apply();
+    }
+
+    #[func]
+    /// Applies the settings of a single category, e.g. `graphics`
+    fn apply_category(&mut self, category: GString) {
+        match category.to_string().as_str() {
+            Self::CATEGORY_GFX => self.graphics.bind().apply(),
+            Self::CATEGORY_AUDIO => self.audio.bind().apply(),
+            Self::CATEGORY_INPUT => {
+                self.input.bind().apply();
+                // Reloading the input map resets the deadzones
+                self.controls.bind().apply();
+            }
+            Self::CATEGORY_CONTROLS => self.controls.bind().apply(),
+            Self::CATEGORY_ACCESSIBILITY => self.accessibility.bind_mut().apply(),
+            _ => godot_warn!("Unknown settings category {}", category),
+        }
+    }
+
+    #[func]
+    /// Writes every setting that differs from the project defaults to the user settings file. Returns false if writing failed
+    ///
+    /// Graphics are compared against the selected preset instead, so only the player's tweaks on top of it are stored
+    fn save(&self) -> bool {
+        let mut categories = BTreeMap::new();
+        for category in &self.categories {
+            let mut values = overrides::diff(&self.baseline_of(category), &category.current);
+            if category.name == Self::CATEGORY_GFX {
+                values.insert(
+                    Self::PRESET_KEY.to_string(),
+                    var_to_str(&self.get_graphics_preset().to_variant()).to_string(),
+                );
+            }
+            for (key, saved) in self
+                .cmdline_shadowed
+                .get(category.name)
+                .into_iter()
+                .flatten()
+            {
+                match saved {
+                    Some(saved) => values.insert(key.clone(), saved.clone()),
+                    None => values.remove(key),
+                };
+            }
+            if !values.is_empty() {
+                categories.insert(category.name.to_string(), values);
+            }
+        }
+        SettingsOverrides { categories }.write(&Self::user_settings_path())
+    }
+
+    #[func]
+    fn get_graphics_preset(&self) -> GraphicsPreset {
+        self.graphics.bind().preset()
+    }
+
+    #[func]
+    /// Switches to a graphics preset. With `keep_overrides` the values the player tweaked on top of the previous preset are carried over to the new one
+    fn set_graphics_preset(&mut self, preset: GraphicsPreset, keep_overrides: bool) {
+        let tweaks = self
+            .categories
+            .iter()
+            .find(|category| category.name == Self::CATEGORY_GFX)
+            .filter(|_| keep_overrides)
+            .map(|category| overrides::diff(&self.baseline_of(category), &category.current));
+        self.graphics.bind_mut().apply_preset(preset);
+        if let Some(tweaks) = tweaks {
+            let mut current = self.graphics.clone().upcast();
+            overrides::apply(Self::CATEGORY_GFX, &mut current, &tweaks);
+        }
+        self.base_mut()
+            .emit_signal("settings_changed", &[Self::CATEGORY_GFX.to_variant()]);
+    }
+
+    #[func]
+    /// The preset that would be picked for this machine on first run
+    fn detect_graphics_preset(&self) -> GraphicsPreset {
+        presets::detect_preset()
+    }
+
+    #[func]
+    /// Puts every setting back to the project defaults. Call `save` to also forget the player's changes on disk and `apply` to see the result
+    fn reset_to_defaults(&mut self) {
+        let categories = self
+            .categories
+            .iter()
+            .map(|category| {
+                (
+                    category.name,
+                    category.defaults.clone(),
+                    category.current.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        for (name, defaults, mut current) in categories {
+            overrides::copy(&defaults, &mut current);
+            self.base_mut()
+                .emit_signal("settings_changed", &[name.to_variant()]);
+        }
+    }
+
+    #[func]
+    /// The names of every settings category, e.g. `graphics`
+    fn get_categories(&self) -> PackedStringArray {
+        self.categories
+            .iter()
+            .map(|category| GString::from(category.name))
+            .collect()
+    }
+
+    #[func]
+    /// The current value of a setting, or null if the category or setting doesn't exist
+    fn get_value(&self, category: GString, key: StringName) -> Variant {
+        self.find_category(&category)
+            .map(|category| category.current.get(&key))
+            .unwrap_or_default()
+    }
+
+    #[func]
+    /// Changes a single setting and emits `settings_changed`. Returns false if the setting doesn't exist or the value has the wrong type
+    fn set_value(&mut self, category: GString, key: StringName, value: Variant) -> bool {
+        let Some(mut current) = self
+            .find_category(&category)
+            .map(|category| category.current.clone())
+        else {
+            return false;
+        };
+        if !overrides::setting_names(&current).contains(&key)
+            || current.get(&key).get_type() != value.get_type()
+        {
+            return false;
+        }
+        current.set(&key, &value);
+        self.base_mut()
+            .emit_signal("settings_changed", &[category.to_variant()]);
+        true
+    }
+
+    #[func]
+    /// The live settings resource of a category, e.g. `graphics`, or null if there is no such category
+    fn get_category(&self, category: GString) -> Option<Gd<Resource>> {
+        self.find_category(&category)
+            .map(|category| category.current.clone())
+    }
+
+    #[func]
+    /// Reloads project defaults whose file changed on disk. Runs every frame while watching but only checks the files once per `POLL_INTERVAL_MSEC`
+    fn poll_settings_files(&mut self) {
+        let now = Time::singleton().get_ticks_msec();
+        if !self.hot_reload || now < self.next_poll_msec {
+            return;
+        }
+        self.next_poll_msec = now + Self::POLL_INTERVAL_MSEC;
+        let modified = self
+            .categories
+            .iter_mut()
+            .filter_map(|category| {
+                let modified_time = FileAccess::get_modified_time(&category.defaults.get_path());
+                (modified_time != category.modified_time).then(|| {
+                    category.modified_time = modified_time;
+                    category.name
+                })
+            })
+            .collect::<Vec<_>>();
+        for name in modified {
+            self.reload_defaults(name);
+        }
+    }
+
+    #[func]
+    /// Called when the project defaults of a category emit `changed`, e.g. when edited from a tool script
+    fn on_defaults_changed(&mut self, category: GString) {
+        if !self.hot_reload {
+            return;
+        }
+        if let Some(name) = self.find_category(&category).map(|category| category.name) {
+            self.rebuild_category(name);
+        }
+    }
+
+    #[func]
+    /// Called when the live settings of a category emit `changed`, e.g. after rebinding an action
+    fn on_current_changed(&mut self, category: GString) {
+        if !self.hot_reload {
+            return;
+        }
+        self.apply_category(category.clone());
+        self.base_mut()
+            .emit_signal("settings_changed", &[category.to_variant()]);
+    }
+
+    fn find_category(&self, name: &GString) -> Option<&SettingsCategory> {
+        self.categories
+            .iter()
+            .find(|category| *name == GString::from(category.name))
+    }
+
+    /// What a category's current values are compared against when saving. For graphics that is the project defaults with the selected preset applied
+    fn baseline_of(&self, category: &SettingsCategory) -> Gd<Resource> {
+        if category.name != Self::CATEGORY_GFX {
+            return category.defaults.clone();
+        }
+        let Some(mut baseline) = category
+            .defaults
+            .duplicate()
+            .and_then(|baseline| baseline.try_cast::<GraphicsSettings>().ok())
+        else {
+            return category.defaults.clone();
+        };
+        baseline.bind_mut().apply_preset(self.get_graphics_preset());
+        baseline.upcast()
     }
+
+    /// Layers the player's saved changes on top of the project defaults
+    ///
+    /// The graphics preset is applied first so the player's tweaks end up on top of it. Players who never saved a preset get one detected for their machine, which is saved right away so detection only runs on the first launch
+    fn load_overrides(&mut self) -> SettingsOverrides {
+        let mut overrides = SettingsOverrides::read(&Self::user_settings_path());
+        let saved_preset = overrides
+            .categories
+            .get(Self::CATEGORY_GFX)
+            .and_then(|values| values.get(Self::PRESET_KEY))
+            .and_then(|preset| str_to_var(preset.as_str()).try_to::<GraphicsPreset>().ok());
+        let preset = match saved_preset {
+            Some(preset) => Some(preset),
+            None if Engine::singleton().is_editor_hint() => None,
+            None => Some(presets::detect_preset()),
+        };
+        if let Some(preset) = preset {
+            self.graphics.bind_mut().apply_preset(preset);
+        }
+        for (name, values) in &overrides.categories {
+            let Some(category) = self
+                .categories
+                .iter_mut()
+                .find(|category| category.name == name)
+            else {
+                godot_warn!("Ignoring unknown settings category {}", name);
+                continue;
+            };
+            overrides::apply(name, &mut category.current, values);
+        }
+        if let (None, Some(preset)) = (saved_preset, preset) {
+            overrides
+                .categories
+                .entry(Self::CATEGORY_GFX.to_string())
+                .or_default()
+                .insert(
+                    Self::PRESET_KEY.to_string(),
+                    var_to_str(&preset.to_variant()).to_string(),
+                );
+            overrides.write(&Self::user_settings_path());
+        }
+        overrides
+    }
+
+    /// Applies `--category.key=value` overrides from the user arguments, e.g. `-- --gfx.sdfgi_enabled=false`. `gfx` is accepted for `graphics`
+    ///
+    /// Overrides are meant for tests and benchmarks, so they are never saved. They take effect along with the rest of the settings when [GameSettings] starts. Unknown settings and values of the wrong type are reported as errors. `only` limits them to a single category
+    fn apply_cmdline_overrides(&mut self, saved: &SettingsOverrides, only: Option<&str>) {
+        for arg in cmdline::parse(&Os::singleton().get_cmdline_user_args()) {
+            if only.is_some_and(|only| only != arg.category) {
+                continue;
+            }
+            let Some(category) = self
+                .categories
+                .iter()
+                .find(|category| category.name == arg.category)
+            else {
+                godot_error!(
+                    "Unknown settings category {} in --{}.{}",
+                    arg.category,
+                    arg.category,
+                    arg.key
+                );
+                continue;
+            };
+            let mut current = category.current.clone();
+            let key = StringName::from(arg.key.as_str());
+            let Some(property) =
+                overrides::setting_properties(&current)
+                    .into_iter()
+                    .find(|property| {
+                        property
+                            .get("name")
+                            .is_some_and(|name| name.to_string() == arg.key)
+                    })
+            else {
+                godot_error!("Unknown setting {}.{}", arg.category, arg.key);
+                continue;
+            };
+            let value = match overrides::parse_value(&current.get(&key), &property, &arg.value) {
+                Ok(value) => value,
+                Err(err) => {
+                    godot_error!(
+                        "Invalid value {} for setting {}.{}: {}",
+                        arg.value,
+                        arg.category,
+                        arg.key,
+                        err
+                    );
+                    continue;
+                }
+            };
+            match value.try_to::<GraphicsPreset>() {
+                Ok(preset)
+                    if category.name == Self::CATEGORY_GFX && arg.key == Self::PRESET_KEY =>
+                {
+                    self.graphics.bind_mut().apply_preset(preset)
+                }
+                _ => current.set(&key, &value),
+            }
+            let previous = saved
+                .categories
+                .get(&arg.category)
+                .and_then(|values| values.get(&arg.key))
+                .cloned();
+            self.cmdline_shadowed
+                .entry(arg.category)
+                .or_default()
+                .insert(arg.key, previous);
+        }
+    }
+
+    /// Connects to the `changed` signal of every settings resource and starts polling the project defaults for changes on disk
+    fn watch_resources(&mut self) {
+        if self.is_watching || !self.hot_reload {
+            return;
+        }
+        let Some(mut tree) = engine::get_scene_tree() else {
+            return;
+        };
+        let gd = self.to_gd();
+        tree.connect(
+            "process_frame",
+            &Callable::from_object_method(&gd, "poll_settings_files"),
+        );
+        for category in &mut self.categories {
+            let name = varray![category.name];
+            category.defaults.connect(
+                "changed",
+                &Callable::from_object_method(&gd, "on_defaults_changed").bindv(&name),
+            );
+            category.current.connect(
+                "changed",
+                &Callable::from_object_method(&gd, "on_current_changed").bindv(&name),
+            );
+        }
+        self.is_watching = true;
+    }
+
+    /// Loads the project defaults of a category from disk again. The cached resource is refreshed in place so everything holding it sees the new values
+    fn reload_defaults(&mut self, name: &'static str) {
+        let Some((path, previous)) = self
+            .categories
+            .iter()
+            .find(|category| category.name == name)
+            .map(|category| (category.defaults.get_path(), category.defaults.clone()))
+        else {
+            return;
+        };
+        let Some(mut defaults) = ResourceLoader::singleton()
+            .load_ex(&path)
+            .cache_mode(CacheMode::REPLACE)
+            .done()
+        else {
+            godot_error!("Failed to reload settings {}", path);
+            return;
+        };
+        if defaults != previous {
+            let gd = self.to_gd();
+            defaults.connect(
+                "changed",
+                &Callable::from_object_method(&gd, "on_defaults_changed").bindv(&varray![name]),
+            );
+        }
+        if let Some(category) = self
+            .categories
+            .iter_mut()
+            .find(|category| category.name == name)
+        {
+            category.defaults = defaults;
+        }
+        print_verbose!("Reloaded {} settings from {}", name, path);
+        self.rebuild_category(name);
+    }
+
+    /// Rebuilds a category's live settings from its project defaults and applies them
+    ///
+    /// The player's saved changes, the graphics preset and command line overrides are layered on top again. Changes that weren't saved yet are lost
+    fn rebuild_category(&mut self, name: &'static str) {
+        let Some(category) = self
+            .categories
+            .iter()
+            .find(|category| category.name == name)
+        else {
+            return;
+        };
+        let defaults = category.defaults.clone();
+        let mut current = category.current.clone();
+        let preset = self.get_graphics_preset();
+        overrides::copy(&defaults, &mut current);
+        if name == Self::CATEGORY_GFX {
+            self.graphics.bind_mut().apply_preset(preset);
+        }
+        let saved = SettingsOverrides::read(&Self::user_settings_path());
+        if let Some(values) = saved.categories.get(name) {
+            overrides::apply(name, &mut current, values);
+        }
+        self.apply_cmdline_overrides(&saved, Some(name));
+        self.apply_category(name.into());
+        self.base_mut()
+            .emit_signal("settings_changed", &[name.to_variant()]);
+    }
+
+    fn user_settings_path() -> String {
+        get_setting_or_default(Self::SETTINGS_USER_PATH, "user://settings.ron".to_variant())
+            .to_string()
+    }
+}
+
+/// Loads a category's project defaults and duplicates them so the player's changes never touch the shared resource
+fn load_category<T>(
+    categories: &mut Vec<SettingsCategory>,
+    name: &'static str,
+    setting: &str,
+    fallback: &str,
+) -> Gd<T>
+where
+    T: GodotClass + Inherits<Resource>,
+    Gd<T>: Default,
+{
+    let path = GString::from_variant(&get_setting_or_default(setting, fallback.to_variant()));
+    let defaults = try_load::<T>(&path).unwrap_or_default();
+    let current = defaults
+        .clone()
+        .upcast::<Resource>()
+        .duplicate()
+        .and_then(|current| current.try_cast::<T>().ok())
+        .unwrap_or_default();
+    categories.push(SettingsCategory {
+        name,
+        defaults: defaults.upcast(),
+        current: current.clone().upcast(),
+        modified_time: FileAccess::get_modified_time(&path),
+    });
+    current
 }
 
 fn get_setting_or_default(name: impl AsArg<GString> + Clone, fallback: Variant) -> Variant {
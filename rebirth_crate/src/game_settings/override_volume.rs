@@ -0,0 +1,346 @@
+use godot::classes::{
+    Area3D, Camera3D, Environment, IArea3D, PhysicsPointQueryParameters3D, Viewport,
+};
+use godot::{obj::WithBaseField, prelude::*};
+
+use crate::{
+    component_utils::{RustyComponent, RustyComponents, SystemTick},
+    engine_helpers::CrateSingleton,
+};
+
+use super::{graphics::find_camera_attributes, GameSettings};
+
+/// Registers the [ComponentSystem](crate::component_utils::ComponentSystem) that blends every [GraphicsOverrideVolume]
+pub fn add_system() {
+    if RustyComponents::add_system::<GraphicsOverrideVolume, _>(
+        SystemTick::Physics,
+        0,
+        GraphicsOverrideVolume::tick_volumes,
+    )
+    .is_none()
+    {
+        godot_error!(
+            "Failed to add the GraphicsOverrideVolume system, volumes will have no effect"
+        );
+    }
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Area3D, init)]
+/// Changes fog, SSAO and exposure while the camera is inside the area, e.g. to darken a cave
+///
+/// The overrides are blended in on entry and back out on exit. They are layered on top of the player's [GraphicsSettings](super::graphics::GraphicsSettings) by changing the camera's environment and camera attributes at runtime, so the settings resources are never touched. Volumes only tune effects: whether an effect is enabled at all is still up to the player. Overlapping volumes are layered by `priority`
+///
+/// The camera is found with a point query against the area's collision layer, so the layer must not be empty. All volumes are blended by a single component system each physics tick
+pub struct GraphicsOverrideVolume {
+    #[export]
+    /// Overlapping volumes with a higher priority are layered on top
+    priority: i32,
+    #[export(range = (0.0, 10.0))]
+    #[init(val = 1.0)]
+    /// Seconds it takes to fully blend in or out. 0 switches instantly
+    blend_time: f32,
+    //
+    //  Fog
+    //
+    #[export]
+    override_fog: bool,
+    #[export(range = (0.0, 1.0))]
+    #[init(val = 0.01)]
+    fog_density: f32,
+    #[export]
+    #[init(val = Color::from_rgb(0.518, 0.553, 0.608))]
+    fog_light_color: Color,
+    #[export]
+    override_volumetric_fog: bool,
+    #[export(range = (0.0, 1.0))]
+    #[init(val = 0.05)]
+    volumetric_fog_density: f32,
+    //
+    //  Ambient occlusion
+    //
+    #[export]
+    override_ssao: bool,
+    #[export(range = (0.0, 16.0))]
+    #[init(val = 2.0)]
+    ssao_intensity: f32,
+    //
+    //  Exposure
+    //
+    #[export]
+    override_exposure: bool,
+    #[export(range = (0.0, 16.0))]
+    #[init(val = 1.0)]
+    /// Multiplies the exposure from the player's graphics settings
+    exposure_scale: f32,
+
+    /// How far the overrides are blended in, from 0 to 1
+    weight: f32,
+    base: Base<Area3D>,
+}
+
+/// The values of a [GraphicsOverrideVolume] at its current blend weight
+#[derive(Debug, Clone, Copy)]
+struct OverrideLayer {
+    priority: i32,
+    weight: f32,
+    fog: Option<(f32, Color)>,
+    volumetric_fog_density: Option<f32>,
+    ssao_intensity: Option<f32>,
+    exposure_scale: Option<f32>,
+}
+
+/// The environment values volumes change, as authored in the scene
+#[derive(Debug, Clone, Copy)]
+struct EnvironmentValues {
+    fog_density: f32,
+    fog_light_color: Color,
+    volumetric_fog_density: f32,
+    ssao_intensity: f32,
+}
+
+impl EnvironmentValues {
+    fn read(env: &Gd<Environment>) -> Self {
+        Self {
+            fog_density: env.get_fog_density(),
+            fog_light_color: env.get_fog_light_color(),
+            volumetric_fog_density: env.get_volumetric_fog_density(),
+            ssao_intensity: env.get_ssao_intensity(),
+        }
+    }
+
+    fn write(&self, env: &mut Gd<Environment>) {
+        env.set_fog_density(self.fog_density);
+        env.set_fog_light_color(self.fog_light_color);
+        env.set_volumetric_fog_density(self.volumetric_fog_density);
+        env.set_ssao_intensity(self.ssao_intensity);
+    }
+
+    fn to_dictionary(self) -> Dictionary {
+        dict! {
+            "fog_density": self.fog_density,
+            "fog_light_color": self.fog_light_color,
+            "volumetric_fog_density": self.volumetric_fog_density,
+            "ssao_intensity": self.ssao_intensity,
+        }
+    }
+
+    fn from_dictionary(values: &Dictionary) -> Option<Self> {
+        Some(Self {
+            fog_density: values.get("fog_density")?.try_to().ok()?,
+            fog_light_color: values.get("fog_light_color")?.try_to().ok()?,
+            volumetric_fog_density: values.get("volumetric_fog_density")?.try_to().ok()?,
+            ssao_intensity: values.get("ssao_intensity")?.try_to().ok()?,
+        })
+    }
+}
+
+impl RustyComponent<Area3D> for GraphicsOverrideVolume {}
+
+#[godot_api]
+impl IArea3D for GraphicsOverrideVolume {
+    fn ready(&mut self) {
+        self.on_ready();
+        if self.base().get_collision_layer() == 0 {
+            godot_warn!(
+                "GraphicsOverrideVolume {} has no collision layer and will never detect the camera",
+                self.base().get_name()
+            );
+        }
+    }
+
+    /// Volumes that leave the tree are no longer ticked, so the rest are layered again right away
+    fn exit_tree(&mut self) {
+        if self.weight <= 0.0 {
+            return;
+        }
+        self.weight = 0.0;
+        let Some(viewport) = self.base().get_viewport() else {
+            return;
+        };
+        let this = self.to_gd();
+        let others = RustyComponents::get_all_components::<Self>()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|volume| *volume != this)
+            .collect::<Vec<_>>();
+        Self::apply_layers(&viewport, Self::find_layers(&others, &viewport));
+    }
+}
+
+#[godot_api]
+impl GraphicsOverrideVolume {
+    /// Where the environment's authored values are kept while volumes change them
+    const BASELINE_META: &'static str = "_graphics_override_baseline";
+
+    #[func]
+    /// How far the overrides are currently blended in, from 0 to 1
+    fn get_weight(&self) -> f32 {
+        self.weight
+    }
+
+    /// Moves the weight toward whether the camera is inside. Returns whether the volume needs to be applied, which is while it is blended in at all since applying the graphics settings resets the exposure
+    fn update_weight(&mut self, delta: f64) -> bool {
+        let target = if self.contains_camera() { 1.0 } else { 0.0 };
+        let previous = self.weight;
+        self.weight = match self.blend_time {
+            time if time <= 0.0 => target,
+            time => move_toward(self.weight, target, delta as f32 / time),
+        };
+        self.weight != previous || self.weight > 0.0
+    }
+
+    fn layer(&self) -> OverrideLayer {
+        OverrideLayer {
+            priority: self.priority,
+            weight: self.weight,
+            fog: self
+                .override_fog
+                .then_some((self.fog_density, self.fog_light_color)),
+            volumetric_fog_density: self
+                .override_volumetric_fog
+                .then_some(self.volumetric_fog_density),
+            ssao_intensity: self.override_ssao.then_some(self.ssao_intensity),
+            exposure_scale: self.override_exposure.then_some(self.exposure_scale),
+        }
+    }
+
+    fn camera(&self) -> Option<Gd<Camera3D>> {
+        self.base().get_viewport()?.get_camera_3d()
+    }
+
+    /// Whether the current camera of this volume's viewport is inside any of its shapes
+    fn contains_camera(&self) -> bool {
+        let Some(camera) = self.camera() else {
+            return false;
+        };
+        let Some(mut space) = self
+            .base()
+            .get_world_3d()
+            .and_then(|mut world| world.get_direct_space_state())
+        else {
+            return false;
+        };
+        let mut query = PhysicsPointQueryParameters3D::new_gd();
+        query.set_position(camera.get_global_position());
+        query.set_collision_mask(self.base().get_collision_layer());
+        query.set_collide_with_areas(true);
+        query.set_collide_with_bodies(false);
+        let this = self.to_gd().upcast::<Object>();
+        space.intersect_point(&query).iter_shared().any(|hit| {
+            hit.get("collider")
+                .and_then(|collider| collider.try_to::<Gd<Object>>().ok())
+                .is_some_and(|collider| collider == this)
+        })
+    }
+
+    /// Updates every volume and layers the blended in ones onto each viewport that changed
+    fn tick_volumes(volumes: Vec<Gd<Self>>, delta: f64) {
+        let mut viewports: Vec<Gd<Viewport>> = Vec::new();
+        for volume in &volumes {
+            if !volume.clone().bind_mut().update_weight(delta) {
+                continue;
+            }
+            if let Some(viewport) = volume.get_viewport() {
+                if !viewports.contains(&viewport) {
+                    viewports.push(viewport);
+                }
+            }
+        }
+        for viewport in viewports {
+            let layers = Self::find_layers(&volumes, &viewport);
+            Self::apply_layers(&viewport, layers);
+        }
+    }
+
+    /// The layers of the volumes in the viewport that are at least partially blended in, lowest priority first
+    fn find_layers(volumes: &[Gd<Self>], viewport: &Gd<Viewport>) -> Vec<OverrideLayer> {
+        let mut layers = volumes
+            .iter()
+            .filter(|volume| volume.get_viewport().as_ref() == Some(viewport))
+            .map(|volume| volume.bind().layer())
+            .filter(|layer| layer.weight > 0.0)
+            .collect::<Vec<_>>();
+        layers.sort_by_key(|layer| layer.priority);
+        layers
+    }
+
+    /// Layers the volumes onto the environment and camera attributes of the viewport's camera, or restores them once no layers are left
+    fn apply_layers(viewport: &Gd<Viewport>, layers: Vec<OverrideLayer>) {
+        let camera = viewport.get_camera_3d();
+        let world = viewport.find_world_3d();
+        let env = camera
+            .as_ref()
+            .and_then(|camera| camera.get_environment())
+            .or_else(|| world.as_ref().and_then(|world| world.get_environment()));
+        if let Some(mut env) = env {
+            Self::apply_environment(&mut env, &layers);
+        }
+
+        let graphics = GameSettings::singleton().map(|settings| settings.bind().graphics.clone());
+        let attributes = find_camera_attributes(camera, None, world, false);
+        if let (Some(graphics), Some(attributes)) = (graphics, attributes) {
+            let exposure_scale =
+                layers
+                    .iter()
+                    .fold(1.0, |scale, layer| match layer.exposure_scale {
+                        Some(target) => lerp(scale, target, layer.weight),
+                        None => scale,
+                    });
+            graphics
+                .bind()
+                .apply_camera_attributes(attributes, exposure_scale);
+        }
+    }
+
+    /// Blends the layers onto the environment's authored values, which are remembered on the environment until the last layer is gone
+    fn apply_environment(env: &mut Gd<Environment>, layers: &[OverrideLayer]) {
+        let stored = env
+            .get_meta_ex(Self::BASELINE_META)
+            .default(&Variant::nil())
+            .done()
+            .try_to::<Dictionary>()
+            .ok()
+            .and_then(|values| EnvironmentValues::from_dictionary(&values));
+        if layers.is_empty() {
+            if let Some(baseline) = stored {
+                baseline.write(env);
+                env.remove_meta(Self::BASELINE_META);
+            }
+            return;
+        }
+        let baseline = stored.unwrap_or_else(|| {
+            let baseline = EnvironmentValues::read(env);
+            env.set_meta(Self::BASELINE_META, &baseline.to_dictionary().to_variant());
+            baseline
+        });
+
+        let mut values = baseline;
+        for layer in layers {
+            if let Some((density, color)) = layer.fog {
+                values.fog_density = lerp(values.fog_density, density, layer.weight);
+                values.fog_light_color = values.fog_light_color.lerp(color, layer.weight as f64);
+            }
+            if let Some(density) = layer.volumetric_fog_density {
+                values.volumetric_fog_density =
+                    lerp(values.volumetric_fog_density, density, layer.weight);
+            }
+            if let Some(intensity) = layer.ssao_intensity {
+                values.ssao_intensity = lerp(values.ssao_intensity, intensity, layer.weight);
+            }
+        }
+        values.write(env);
+    }
+}
+
+fn lerp(from: f32, to: f32, weight: f32) -> f32 {
+    from + (to - from) * weight
+}
+
+fn move_toward(from: f32, to: f32, delta: f32) -> f32 {
+    if (to - from).abs() <= delta {
+        to
+    } else {
+        from + (to - from).signum() * delta
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+
+use godot::{
+    classes::{file_access::ModeFlags, FileAccess},
+    global::{str_to_var, var_to_str, PropertyHint, PropertyUsageFlags},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::versioning::{Migration, VersionError, VersionedFormat};
+
+/// Migrations for [SettingsOverrides], oldest first
+const SETTINGS_MIGRATIONS: &[Migration] = &[];
+const SETTINGS_FORMAT: VersionedFormat = VersionedFormat::new(SETTINGS_MIGRATIONS);
+
+/// Every setting the player changed from the project defaults, keyed by category and then property name
+///
+/// Values are stored in godot's `var_to_str` format so any exported property survives the round trip. Only changed values are stored, so new project defaults still reach players who never touched that setting
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SettingsOverrides {
+    pub categories: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl SettingsOverrides {
+    /// Reads the overrides file. A missing file is not an error since nothing has been changed yet
+    pub fn read(path: &str) -> Self {
+        if !FileAccess::file_exists(path) {
+            return Self::default();
+        }
+        let Some(file) = FileAccess::open(path, ModeFlags::READ) else {
+            godot_error!(
+                "Failed to open settings {}: {}",
+                path,
+                FileAccess::get_open_error().godot_name()
+            );
+            return Self::default();
+        };
+        match Self::parse(&file.get_as_text().to_string()) {
+            Ok(overrides) => overrides,
+            Err(err) => {
+                godot_error!("Failed to parse settings {}: {}", path, err);
+                Self::default()
+            }
+        }
+    }
+
+    /// Parses the contents of an overrides file, migrating older files to the current format
+    pub fn parse(text: &str) -> Result<Self, VersionError> {
+        SETTINGS_FORMAT.read(text)
+    }
+
+    pub fn write(&self, path: &str) -> bool {
+        let data = match SETTINGS_FORMAT.write(self) {
+            Ok(data) => data,
+            Err(err) => {
+                godot_error!("Failed to serialize settings: {}", err);
+                return false;
+            }
+        };
+        let Some(mut file) = FileAccess::open(path, ModeFlags::WRITE) else {
+            godot_error!(
+                "Failed to open {} for writing: {}",
+                path,
+                FileAccess::get_open_error().godot_name()
+            );
+            return false;
+        };
+        file.store_string(&data);
+        file.close();
+        true
+    }
+}
+
+/// The property info of every exported setting on a settings resource, skipping the properties every [Resource] has
+pub fn setting_properties(resource: &Gd<Resource>) -> Vec<Dictionary> {
+    let required = PropertyUsageFlags::STORAGE.ord() | PropertyUsageFlags::EDITOR.ord();
+    resource
+        .get_property_list()
+        .iter_shared()
+        .filter(|property| {
+            let usage = property
+                .get("usage")
+                .and_then(|usage| usage.try_to::<u64>().ok())
+                .unwrap_or_default();
+            let name = property.get("name").unwrap_or_default().to_string();
+            usage & required == required && name != "script" && !name.starts_with("resource_")
+        })
+        .collect()
+}
+
+/// The names of every exported setting on a settings resource. See [setting_properties]
+pub fn setting_names(resource: &Gd<Resource>) -> Vec<StringName> {
+    setting_properties(resource)
+        .iter()
+        .filter_map(|property| property.get("name")?.try_to::<StringName>().ok())
+        .collect()
+}
+
+/// The names and values of an enum property's hint string, e.g. `None:0,X2:1`. Items without an explicit value are numbered by position
+pub fn enum_items(hint_string: &str) -> Vec<(String, i32)> {
+    hint_string
+        .split(',')
+        .enumerate()
+        .map(|(index, item)| match item.rsplit_once(':') {
+            Some((label, id)) => (
+                label.trim().to_string(),
+                id.trim().parse().unwrap_or(index as i32),
+            ),
+            None => (item.trim().to_string(), index as i32),
+        })
+        .collect()
+}
+
+/// Parses text into a value for the given setting, e.g. from the command line
+///
+/// Text is read as a godot literal (`false`, `0.5`, `Vector2i(1280, 720)`), enums also accept their item names and strings are taken as is
+pub fn parse_value(
+    current: &Variant,
+    property: &Dictionary,
+    text: &str,
+) -> Result<Variant, String> {
+    let expected = current.get_type();
+    let hint = property
+        .get("hint")
+        .and_then(|hint| hint.try_to::<i32>().ok())
+        .unwrap_or_default();
+    if expected == VariantType::INT && hint == PropertyHint::ENUM.ord() {
+        let hint_string = property.get("hint_string").unwrap_or_default().to_string();
+        let items = enum_items(&hint_string);
+        if let Some((_, id)) = items
+            .iter()
+            .find(|(label, _)| label.eq_ignore_ascii_case(text))
+        {
+            return Ok(id.to_variant());
+        }
+    }
+    match expected {
+        VariantType::STRING => return Ok(GString::from(text).to_variant()),
+        VariantType::STRING_NAME => return Ok(StringName::from(text).to_variant()),
+        _ => {}
+    }
+    let value = str_to_var(text);
+    match (expected, value.get_type()) {
+        (expected, found) if expected == found => Ok(value),
+        (VariantType::FLOAT, VariantType::INT) => Ok((value.to::<i64>() as f64).to_variant()),
+        (expected, found) => Err(format!("expected {:?} but found {:?}", expected, found)),
+    }
+}
+
+/// Every setting whose value differs from the defaults
+pub fn diff(defaults: &Gd<Resource>, current: &Gd<Resource>) -> BTreeMap<String, String> {
+    setting_names(current)
+        .into_iter()
+        .filter_map(|name| {
+            let value = current.get(&name);
+            (value != defaults.get(&name))
+                .then(|| (name.to_string(), var_to_str(&value).to_string()))
+        })
+        .collect()
+}
+
+/// Sets each stored value on the resource. Unknown settings and values of the wrong type are skipped with a warning
+pub fn apply(category: &str, current: &mut Gd<Resource>, values: &BTreeMap<String, String>) {
+    let names = setting_names(current);
+    for (name, value) in values {
+        let name = StringName::from(name.as_str());
+        if !names.contains(&name) {
+            godot_warn!("Ignoring unknown setting {}.{}", category, name);
+            continue;
+        }
+        let value = str_to_var(value.as_str());
+        let expected = current.get(&name).get_type();
+        if value.get_type() != expected {
+            godot_warn!(
+                "Ignoring setting {}.{}: expected {:?} but found {:?}",
+                category,
+                name,
+                expected,
+                value.get_type()
+            );
+            continue;
+        }
+        current.set(&name, &value);
+    }
+}
+
+/// Copies every setting from one resource onto another of the same class
+pub fn copy(from: &Gd<Resource>, to: &mut Gd<Resource>) {
+    for name in setting_names(from) {
+        to.set(&name, &from.get(&name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::versioning::testing::{rename_field, with_migration};
+
+    const LEGACY: &str = include_str!("../../tests/fixtures/settings/legacy.ron");
+    const CURRENT: &str = include_str!("../../tests/fixtures/settings/current.ron");
+    /// Written at the current version, but with `categories` still called `settings`
+    const OLDER: &str = include_str!("../../tests/fixtures/settings/older.ron");
+
+    fn assert_overrides(overrides: &SettingsOverrides) {
+        let graphics = &overrides.categories["graphics"];
+        assert_eq!(graphics["preset"], "2");
+        assert_eq!(graphics["sdfgi_enabled"], "false");
+        assert_eq!(overrides.categories["audio"]["master_volume"], "0.5");
+    }
+
+    #[test]
+    fn parses_legacy_settings() {
+        assert_overrides(&SettingsOverrides::parse(LEGACY).unwrap());
+    }
+
+    #[test]
+    fn parses_current_settings() {
+        assert_overrides(&SettingsOverrides::parse(CURRENT).unwrap());
+    }
+
+    #[test]
+    fn migrates_older_settings() {
+        fn rename_settings(data: ron::Value) -> Result<ron::Value, String> {
+            rename_field(data, &[], "settings", "categories")
+        }
+        assert!(SettingsOverrides::parse(OLDER).is_err());
+        let format = with_migration(&SETTINGS_FORMAT, rename_settings);
+        assert_overrides(&format.read::<SettingsOverrides>(OLDER).unwrap());
+    }
+}
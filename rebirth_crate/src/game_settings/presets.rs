@@ -0,0 +1,66 @@
+use godot::classes::{rendering_device::DeviceType, Os, ProjectSettings, RenderingServer};
+use godot::prelude::*;
+
+use crate::engine_helpers::print_verbose;
+
+#[derive(
+    GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[godot(via=i32)]
+/// A named set of graphics quality values. See [GraphicsSettings::apply_preset](super::graphics::GraphicsSettings::apply_preset)
+pub enum GraphicsPreset {
+    Potato,
+    Low,
+    Medium,
+    High,
+    Ultra,
+    #[default]
+    /// The values authored in the settings resource, untouched by any preset
+    Custom,
+}
+
+/// Picks a preset for the machine the game is running on
+///
+/// Godot doesn't expose the total amount of video memory, so the adapter type stands in for it: integrated GPUs share system memory and discrete GPUs bring their own
+pub fn detect_preset() -> GraphicsPreset {
+    let server = RenderingServer::singleton();
+    let adapter = server.get_video_adapter_name().to_string();
+    let cpus = Os::singleton().get_processor_count();
+    let driver = if server.get_rendering_device().is_some() {
+        ProjectSettings::singleton()
+            .get_setting("rendering/rendering_device/driver")
+            .to_string()
+    } else {
+        "opengl3".to_string()
+    };
+
+    let lower = adapter.to_lowercase();
+    let software = ["llvmpipe", "swiftshader", "software", "microsoft basic"]
+        .iter()
+        .any(|name| lower.contains(name));
+
+    let preset = match server.get_video_adapter_type() {
+        _ if software => GraphicsPreset::Potato,
+        DeviceType::CPU => GraphicsPreset::Potato,
+        DeviceType::DISCRETE_GPU if cpus >= 12 => GraphicsPreset::Ultra,
+        DeviceType::DISCRETE_GPU if cpus >= 6 => GraphicsPreset::High,
+        DeviceType::DISCRETE_GPU => GraphicsPreset::Medium,
+        DeviceType::INTEGRATED_GPU if cpus >= 4 => GraphicsPreset::Low,
+        _ if cpus >= 4 => GraphicsPreset::Low,
+        _ => GraphicsPreset::Potato,
+    };
+    // The compatibility renderer can't do most of the effects the higher presets turn on
+    let preset = if driver == "opengl3" {
+        preset.min(GraphicsPreset::Low)
+    } else {
+        preset
+    };
+    print_verbose!(
+        "Detected graphics preset {:?} for {} ({}, {} CPUs)",
+        preset,
+        adapter,
+        driver,
+        cpus
+    );
+    preset
+}
@@ -0,0 +1,40 @@
+use godot::prelude::*;
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, init, tool)]
+/// A single named verb on an [InteractionComponent](super::InteractionComponent), like "Open", "Lock" or "Examine"
+pub struct InteractionAction {
+    #[export]
+    /// The verb shown to the player, e.g. "open"
+    verb: GString,
+    #[export]
+    #[init(val = "interact".into())]
+    /// The input action that performs this verb directly
+    input_action: StringName,
+    #[export]
+    /// An optional method called on the interactable (the component's parent) when this verb is performed
+    method: StringName,
+    #[export]
+    #[init(val = true)]
+    /// Disabled actions are hidden from interactors
+    enabled: bool,
+    base: Base<Resource>,
+}
+
+impl InteractionAction {
+    pub fn verb(&self) -> GString {
+        self.verb.clone()
+    }
+
+    pub fn input_action(&self) -> StringName {
+        self.input_action.clone()
+    }
+
+    pub fn method(&self) -> StringName {
+        self.method.clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
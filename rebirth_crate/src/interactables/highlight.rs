@@ -0,0 +1,210 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use godot::{
+    classes::{
+        base_material_3d::Feature, BaseMaterial3D, Material, MeshInstance3D, Shader,
+        ShaderMaterial, StandardMaterial3D,
+    },
+    obj::WithBaseField,
+    prelude::*,
+};
+
+use crate::component_utils::{RustyComponent, RustyComponents};
+
+use super::InteractionComponent;
+
+/// An inverted hull outline whose width is measured in pixels of the viewport being rendered to. Since the game renders into a low-res `SubViewport`, this keeps the outline crisp and a consistent thickness after upscaling
+const OUTLINE_SHADER: &str = r#"
+shader_type spatial;
+render_mode unshaded, cull_front, depth_draw_opaque;
+
+uniform vec4 outline_color : source_color = vec4(1.0);
+uniform float outline_pixels = 1.0;
+
+void vertex() {
+	vec4 clip = PROJECTION_MATRIX * MODELVIEW_MATRIX * vec4(VERTEX, 1.0);
+	vec3 clip_normal = mat3(PROJECTION_MATRIX) * (mat3(MODELVIEW_MATRIX) * NORMAL);
+	clip.xy += normalize(clip_normal.xy) / VIEWPORT_SIZE * outline_pixels * 2.0 * clip.w;
+	POSITION = clip;
+}
+
+void fragment() {
+	ALBEDO = outline_color.rgb;
+}
+"#;
+
+/// The instance id of the compiled [OUTLINE_SHADER]. Only the id is kept so the shader is freed with the last outline using it
+static OUTLINE_SHADER_ID: AtomicI64 = AtomicI64::new(0);
+
+/// The shared outline shader, compiled the first time an outline is needed. Sharing it avoids a compile each time a highlight is created
+fn outline_shader() -> Gd<Shader> {
+    let existing = InstanceId::try_from_i64(OUTLINE_SHADER_ID.load(Ordering::Relaxed))
+        .and_then(|id| Gd::<Shader>::try_from_instance_id(id).ok());
+    if let Some(shader) = existing {
+        return shader;
+    }
+    let mut shader = Shader::new_gd();
+    shader.set_code(OUTLINE_SHADER);
+    OUTLINE_SHADER_ID.store(shader.instance_id().to_i64(), Ordering::Relaxed);
+    shader
+}
+
+#[derive(GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[godot(via=i32)]
+/// How an [InteractionHighlight] makes its meshes stand out
+pub enum HighlightMode {
+    #[default]
+    /// Adds an outline as a next pass on every surface material
+    Outline,
+    /// Makes the surface materials glow. Surfaces without a [BaseMaterial3D] get an outline instead
+    Emission,
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Node, init)]
+/// Highlights the sibling [MeshInstance3D]s while the sibling [InteractionComponent] is selected
+pub struct InteractionHighlight {
+    #[export]
+    mode: HighlightMode,
+    #[export]
+    /// A custom material to use as the next pass. When empty a built in pixel-width outline is used
+    outline_material: Option<Gd<Material>>,
+    #[export]
+    #[init(val = Color::WHITE)]
+    outline_color: Color,
+    #[export]
+    #[init(val = 1.0)]
+    /// Outline width in pixels of the rendering viewport
+    outline_pixels: f32,
+    #[export]
+    #[init(val = Color::WHITE)]
+    emission_color: Color,
+    #[export]
+    #[init(val = 0.5)]
+    emission_energy: f32,
+
+    /// The built in outline, created on first use and reused for every highlight after that
+    outline: Option<Gd<ShaderMaterial>>,
+    /// The overrides each surface had before being highlighted, to be restored afterwards
+    originals: Vec<(Gd<MeshInstance3D>, i32, Option<Gd<Material>>)>,
+    base: Base<Node>,
+}
+
+impl RustyComponent<Node> for InteractionHighlight {}
+
+#[godot_api]
+impl INode for InteractionHighlight {
+    fn ready(&mut self) {
+        self.on_ready();
+        let Some(parent) = self.base().get_parent() else {
+            return;
+        };
+        let Some(mut interact) = RustyComponents::get_component::<InteractionComponent>(&parent)
+        else {
+            godot_warn!(
+                "InteractionHighlight on {} has no sibling InteractionComponent",
+                parent.get_name()
+            );
+            return;
+        };
+        let gd = self.to_gd();
+        interact.connect(
+            InteractionComponent::SIGNAL_SELECT,
+            &Callable::from_object_method(&gd, "highlight"),
+        );
+        interact.connect(
+            InteractionComponent::SIGNAL_DESELECT,
+            &Callable::from_object_method(&gd, "unhighlight"),
+        );
+    }
+}
+
+#[godot_api]
+impl InteractionHighlight {
+    #[func]
+    /// Applies the highlight to every sibling mesh. Does nothing if already highlighted
+    pub fn highlight(&mut self) {
+        if !self.originals.is_empty() {
+            return;
+        }
+        let outline = self.outline_material();
+        for mut mesh in self.sibling_meshes() {
+            let Some(surfaces) = mesh.get_mesh().map(|m| m.get_surface_count()) else {
+                continue;
+            };
+            for surface in 0..surfaces {
+                let original = mesh.get_surface_override_material(surface);
+                let highlighted =
+                    self.highlighted_material(mesh.get_active_material(surface), &outline);
+                mesh.set_surface_override_material(surface, &highlighted);
+                self.originals.push((mesh.clone(), surface, original));
+            }
+        }
+    }
+
+    #[func]
+    /// Restores the materials the meshes had before being highlighted
+    pub fn unhighlight(&mut self) {
+        for (mut mesh, surface, original) in self.originals.drain(..) {
+            if mesh.is_instance_valid() {
+                mesh.set_surface_override_material(surface, original.as_ref());
+            }
+        }
+    }
+
+    #[func]
+    pub fn is_highlighted(&self) -> bool {
+        !self.originals.is_empty()
+    }
+
+    fn sibling_meshes(&self) -> Vec<Gd<MeshInstance3D>> {
+        let Some(parent) = self.base().get_parent() else {
+            return Vec::new();
+        };
+        parent
+            .get_children()
+            .iter_shared()
+            .filter_map(|child| child.try_cast::<MeshInstance3D>().ok())
+            .collect()
+    }
+
+    /// The custom outline material, or else the built in outline with the current color and width
+    fn outline_material(&mut self) -> Gd<Material> {
+        if let Some(custom) = &self.outline_material {
+            return custom.clone();
+        }
+        let mut material = self
+            .outline
+            .get_or_insert_with(|| {
+                let mut material = ShaderMaterial::new_gd();
+                material.set_shader(&outline_shader());
+                material
+            })
+            .clone();
+        material.set_shader_parameter("outline_color", &self.outline_color.to_variant());
+        material.set_shader_parameter("outline_pixels", &self.outline_pixels.to_variant());
+        material.upcast()
+    }
+
+    /// A copy of the surface's current material with the highlight applied, so the original is never modified
+    fn highlighted_material(
+        &self,
+        active: Option<Gd<Material>>,
+        outline: &Gd<Material>,
+    ) -> Gd<Material> {
+        let copy = active
+            .and_then(|material| material.duplicate())
+            .and_then(|res| res.try_cast::<Material>().ok());
+        if self.mode == HighlightMode::Emission {
+            if let Some(Ok(mut base)) = copy.clone().map(|m| m.try_cast::<BaseMaterial3D>()) {
+                base.set_feature(Feature::EMISSION, true);
+                base.set_emission(self.emission_color);
+                base.set_emission_energy_multiplier(self.emission_energy);
+                return base.upcast();
+            }
+        }
+        let mut material = copy.unwrap_or_else(|| StandardMaterial3D::new_gd().upcast());
+        material.set_next_pass(outline);
+        material
+    }
+}
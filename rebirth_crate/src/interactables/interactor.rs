@@ -0,0 +1,59 @@
+use godot::{
+    classes::{IRayCast3D, InputEvent, RayCast3D},
+    prelude::*,
+};
+
+use super::{InteractionComponent, InteractionResult, InteractionTarget, RustyInteractor};
+
+#[derive(Debug, GodotClass)]
+#[class(base=RayCast3D, init)]
+/// Targets whichever interactable the ray directly hits
+struct Interactor {
+    #[export(flags = (Channel1 = 1, Channel2 = 2, Channel3 = 4, Channel4 = 8))]
+    #[init(val = 1)]
+    /// Only interactables sharing at least one channel can be targeted
+    interaction_channels: u32,
+    #[export]
+    /// If not empty, only interactables with at least one of these tags can be targeted
+    accepted_tags: PackedStringArray,
+
+    interaction: InteractionTarget,
+    base: Base<RayCast3D>,
+}
+
+impl RustyInteractor<RayCast3D> for Interactor {
+    fn interaction(&self) -> &InteractionTarget {
+        &self.interaction
+    }
+
+    fn interaction_mut(&mut self) -> &mut InteractionTarget {
+        &mut self.interaction
+    }
+
+    fn interaction_channels(&self) -> u32 {
+        self.interaction_channels
+    }
+
+    fn accepted_tags(&self) -> PackedStringArray {
+        self.accepted_tags.clone()
+    }
+}
+
+#[godot_api]
+impl IRayCast3D for Interactor {
+    fn physics_process(&mut self, delta: f64) {
+        let option_collider = self
+            .base()
+            .get_collider()
+            .and_then(|collider| self.interactable_of(collider))
+            .map(|(node, _)| node);
+        self.set_target(option_collider);
+        self.process_hold(delta as f32);
+    }
+
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        self.handle_action_input(&event);
+    }
+}
+
+interactor_api!(Interactor);
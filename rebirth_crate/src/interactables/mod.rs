@@ -1,85 +1,528 @@
 use godot::{
-    classes::{IRayCast3D, RayCast3D},
-    global::Error,
+    classes::{InputEvent, InputEventKey, InputMap, Texture2D},
+    global::Key,
     obj::WithBaseField,
     prelude::*,
 };
 
-use crate::component_utils::{RustyComponent, RustyComponents};
+use crate::{
+    component_utils::{RustyComponent, RustyComponents},
+    engine_helpers::CrateSingleton,
+    event_depot::{EventArgs, EventDepot},
+    game_settings,
+};
 
-#[derive(Debug, GodotClass)]
-#[class(base=RayCast3D, init)]
-struct Interactor {
-    last_interaction: Option<Gd<Node3D>>,
-    base: Base<RayCast3D>,
-}
+use action::InteractionAction;
+use requirement::{InteractionRequirement, InteractionResult, InteractionStatus};
 
-#[godot_api]
-impl IRayCast3D for Interactor {
-    fn physics_process(&mut self, _delta: f64) {
-        let option_collider = self
-            .base()
-            .get_collider()
-            .and_then(|coll| match coll.try_cast::<Node3D>() {
-                Ok(node) => Some(node),
-                Err(_) => None,
-            })
-            .and_then(|n3d| {
-                if RustyComponents::get_component::<InteractionComponent>(&n3d.clone().upcast())
-                    .is_some()
-                {
-                    Some(n3d)
-                } else {
-                    None
+/// Declares the signals and script API every interactor shares, in place of the interactor's own `#[godot_api]` block. The behavior lives in [RustyInteractor]
+macro_rules! interactor_api {
+    ($class:ident) => {
+        #[godot_api]
+        impl $class {
+            #[signal]
+            /// Emitted when the targeted interactable changes, with the prompt for the selected action and every available verb. The prompt is empty when nothing is targeted
+            fn interactable_changed(
+                interactable: Option<Gd<Node>>,
+                prompt: GString,
+                verbs: PackedStringArray,
+            ) {
+            }
+
+            #[signal]
+            /// Emitted when a different action is selected on the current target
+            fn selected_action_changed(index: i32, prompt: GString) {}
+
+            #[signal]
+            /// Emitted when an interaction attempt is refused, e.g. because the target is locked
+            fn interaction_refused(result: Gd<InteractionResult>) {}
+
+            #[signal]
+            /// Emitted every physics tick while a hold interaction is in progress, with progress from 0 to 1
+            fn interaction_progress(progress: f32) {}
+
+            #[signal]
+            /// Emitted when a hold interaction is released early or the target changes
+            fn interaction_cancelled() {}
+
+            #[func]
+            /// Interacts with the current target. For targets with a `hold_duration` this starts the hold instead, which completes in `physics_process`
+            ///
+            /// Returns a result describing whether the interaction happened, its hold started, or why it was refused
+            fn do_interact(&mut self) -> Gd<InteractionResult> {
+                self.try_interact()
+            }
+
+            #[func]
+            /// Performs the target's action bound to the given input action, regardless of which action is selected
+            fn do_action(&mut self, input_action: StringName) -> Gd<InteractionResult> {
+                self.try_action(input_action)
+            }
+
+            #[func]
+            /// Selects the next action on the current target. Pass a negative step to go backwards
+            fn cycle_action(&mut self, step: i32) {
+                RustyInteractor::cycle_action(self, step);
+            }
+
+            #[func]
+            /// Selects an action on the current target by its index in the available verbs
+            fn select_action(&mut self, index: i32) {
+                if let Ok(index) = usize::try_from(index) {
+                    RustyInteractor::select_action(self, index);
                 }
+            }
+
+            #[func]
+            /// The index of the selected action on the current target
+            fn get_selected_action(&self) -> i32 {
+                self.interaction().selected_action as i32
+            }
+
+            #[func]
+            /// Stops any hold interaction in progress without triggering it. Call this when the interact input is released
+            fn cancel_interact(&mut self) {
+                self.cancel_hold();
+            }
+
+            #[func]
+            /// Whether a hold interaction is currently in progress
+            fn is_holding(&self) -> bool {
+                self.interaction().hold_elapsed.is_some()
+            }
+
+            #[func]
+            /// The interaction component of the currently targeted interactable, if any
+            fn get_interaction(&self) -> Option<Gd<InteractionComponent>> {
+                self.current_interaction()
+            }
+
+            #[func]
+            /// The display prompt for the currently targeted interactable, or an empty string if nothing is targeted
+            fn get_prompt(&self) -> GString {
+                self.current_prompt()
+            }
+        }
+    };
+}
+
+mod action;
+mod highlight;
+mod interactor;
+mod requirement;
+mod shape_interactor;
+
+/// The interactable currently targeted by an interactor, along with any hold in progress
+///
+/// Targets are tracked by instance id rather than `Gd` since interactables regularly free themselves, e.g. a picked up item
+#[derive(Debug, Default)]
+struct InteractionTarget {
+    target: Option<InstanceId>,
+    /// The selected component, kept separately so it can still be deselected after the target leaves the tree
+    component: Option<InstanceId>,
+    /// Index into the target's available actions
+    selected_action: usize,
+    /// Seconds the current hold interaction has been held for, if one is in progress
+    hold_elapsed: Option<f32>,
+    /// The action that will be performed once the hold completes
+    hold_action: Option<Gd<InteractionAction>>,
+}
+
+impl InteractionTarget {
+    /// The targeted node, if it hasn't been freed
+    fn target_node(&self) -> Option<Gd<Node3D>> {
+        Gd::try_from_instance_id(self.target?).ok()
+    }
+
+    /// The selected component, if it hasn't been freed
+    fn component(&self) -> Option<Gd<InteractionComponent>> {
+        Gd::try_from_instance_id(self.component?).ok()
+    }
+
+    /// False once the target has been freed or removed from the tree
+    fn is_valid(&self) -> bool {
+        match self.target {
+            None => true,
+            Some(_) => self.target_node().is_some_and(|node| node.is_inside_tree()),
+        }
+    }
+}
+
+/// A rust-side helper trait sharing the select/deselect/interact contract between the different interactor nodes
+///
+/// Implementors only decide which node is targeted each physics tick and pass it to [RustyInteractor::set_target]. Their signals and script API are declared with `interactor_api!`
+trait RustyInteractor<T>: GodotClass + WithBaseField<Base = T>
+where
+    T: GodotClass + Inherits<Node3D>,
+{
+    /// Input action selecting the next action on the target
+    const CYCLE_NEXT: &'static str = "interact_next";
+    /// Input action selecting the previous action on the target
+    const CYCLE_PREVIOUS: &'static str = "interact_previous";
+
+    fn interaction(&self) -> &InteractionTarget;
+    fn interaction_mut(&mut self) -> &mut InteractionTarget;
+    /// The interaction channels this interactor can target, as a bitmask
+    fn interaction_channels(&self) -> u32;
+    /// If not empty, only interactables with at least one of these tags can be targeted
+    fn accepted_tags(&self) -> PackedStringArray;
+
+    /// Whether this interactor is allowed to target the given interaction component at all
+    fn accepts(&self, interact: &Gd<InteractionComponent>) -> bool {
+        let interact = interact.bind();
+        if !interact.enabled || interact.interaction_channels & self.interaction_channels() == 0 {
+            return false;
+        }
+        let accepted = self.accepted_tags();
+        accepted.is_empty()
+            || interact
+                .tags
+                .as_slice()
+                .iter()
+                .any(|tag| accepted.contains(tag))
+    }
+
+    /// Finds the node to target for a collider, if it has an [InteractionComponent] this interactor accepts
+    fn interactable_of(
+        &self,
+        collider: Gd<Object>,
+    ) -> Option<(Gd<Node3D>, Gd<InteractionComponent>)> {
+        let node = collider.try_cast::<Node3D>().ok()?;
+        let interact =
+            RustyComponents::get_component::<InteractionComponent>(&node.clone().upcast())?;
+        if !self.accepts(&interact) {
+            return None;
+        }
+        Some((node, interact))
+    }
+
+    /// Runs `f` behind a `base_mut()` guard, so scripts it ends up calling can call back into this interactor, e.g. `cancel_interact` from an `on_interact` handler
+    ///
+    /// The interactor's state may have changed by the time this returns
+    fn reentrant<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let _guard = self.base_mut();
+        f()
+    }
+
+    fn emit(&mut self, signal: &str, args: &[Variant]) {
+        self.base_mut()
+            .upcast_mut::<Node3D>()
+            .emit_signal(signal, args);
+    }
+
+    /// Updates the targeted interactable, emitting select/deselect as needed
+    ///
+    /// A target that was freed or left the tree is always dropped, deselecting its component if that is still alive
+    fn set_target(&mut self, next: Option<Gd<Node3D>>) {
+        let next_id = next.as_ref().map(|node| node.instance_id());
+        if next_id == self.interaction().target && self.interaction().is_valid() {
+            return;
+        }
+        self.cancel_hold();
+        if let Some(interact) = self.interaction().component() {
+            self.reentrant(|| {
+                InteractionComponent::notify(interact, InteractionComponent::SIGNAL_DESELECT)
+            });
+        }
+        let component = next.and_then(|node| {
+            RustyComponents::get_component::<InteractionComponent>(&node.upcast())
+        });
+        let state = self.interaction_mut();
+        state.target = component.as_ref().and(next_id);
+        state.component = component.as_ref().map(|interact| interact.instance_id());
+        state.selected_action = 0;
+        if let Some(interact) = self.current_interaction() {
+            self.reentrant(|| {
+                InteractionComponent::notify(interact, InteractionComponent::SIGNAL_SELECT)
             });
-        if option_collider == self.last_interaction {
+        }
+        let inter_var = self.interaction().target_node().to_variant();
+        let prompt_var = self.current_prompt().to_variant();
+        let verbs_var = self
+            .current_interaction()
+            .map(|interact| interact.bind().get_verbs())
+            .unwrap_or_default()
+            .to_variant();
+        self.emit("interactable_changed", &[inter_var, prompt_var, verbs_var]);
+    }
+
+    /// Moves the selected action forwards (or backwards for negative steps), wrapping around
+    fn cycle_action(&mut self, step: i32) {
+        let count = self
+            .current_interaction()
+            .map(|interact| interact.bind().available_actions().len())
+            .unwrap_or_default();
+        if count == 0 {
             return;
         }
-        if let Some(last) = self.last_interaction.clone() {
-            if let Some(mut interact) =
-                RustyComponents::get_component::<InteractionComponent>(&last.upcast())
-            {
-                interact.emit_signal(InteractionComponent::SIGNAL_DESELECT, &[]);
-            }
+        let current = self.interaction().selected_action as i64;
+        self.select_action((current + step as i64).rem_euclid(count as i64) as usize);
+    }
+
+    fn select_action(&mut self, index: usize) {
+        let count = self
+            .current_interaction()
+            .map(|interact| interact.bind().available_actions().len())
+            .unwrap_or_default();
+        if index >= count || index == self.interaction().selected_action {
+            return;
         }
-        if let Some(new) = option_collider.clone() {
-            if let Some(mut interact) =
-                RustyComponents::get_component::<InteractionComponent>(&new.upcast())
-            {
-                interact.emit_signal(InteractionComponent::SIGNAL_SELECT, &[]);
+        self.cancel_hold();
+        self.interaction_mut().selected_action = index;
+        let prompt_var = self.current_prompt().to_variant();
+        self.emit(
+            "selected_action_changed",
+            &[(index as i32).to_variant(), prompt_var],
+        );
+    }
+
+    /// Handles cycling between the target's actions and the inputs bound directly to them, marking the event as handled if it was used
+    ///
+    /// Actions bound to the component's own `action_name` are left to `do_interact`, which performs whichever action is selected. Releasing the input of a hold started here cancels it unless hold actions are toggled
+    fn handle_action_input(&mut self, event: &Gd<InputEvent>) {
+        if self.action_input(event) {
+            if let Some(mut viewport) = self.base().upcast_ref::<Node3D>().get_viewport() {
+                viewport.set_input_as_handled();
             }
         }
-        self.last_interaction = option_collider;
-        let inter_var = self.last_interaction.to_variant();
-        self.base_mut()
-            .emit_signal("interactable_changed", &[inter_var]);
     }
-}
-
-#[godot_api]
-impl Interactor {
-    #[signal]
-    fn interactable_changed(interactable: Option<Gd<Node>>) {}
 
-    #[func]
-    fn do_interact(&self) -> bool {
-        let Some(node) = &self.last_interaction else {
+    /// See [RustyInteractor::handle_action_input]. Returns whether the event was used
+    fn action_input(&mut self, event: &Gd<InputEvent>) -> bool {
+        let input_map = InputMap::singleton();
+        let pressed =
+            |action: &StringName| input_map.has_action(action) && event.is_action_pressed(action);
+        let released =
+            |action: &StringName| input_map.has_action(action) && event.is_action_released(action);
+        if pressed(&Self::CYCLE_NEXT.into()) {
+            self.cycle_action(1);
+            return true;
+        }
+        if pressed(&Self::CYCLE_PREVIOUS.into()) {
+            self.cycle_action(-1);
+            return true;
+        }
+        let Some(interact) = self.current_interaction() else {
             return false;
         };
-        let Some(mut interact) =
-            RustyComponents::get_component::<InteractionComponent>(&node.clone().upcast())
-        else {
+        let primary = interact.bind().action_name.clone();
+        let toggle = game_settings::toggle_hold_actions();
+        let holding = self
+            .interaction()
+            .hold_action
+            .as_ref()
+            .map(|action| action.bind().input_action())
+            .filter(|input| *input != primary);
+        if let Some(input) = holding {
+            if released(&input) && !toggle {
+                self.cancel_hold();
+                return true;
+            }
+            if pressed(&input) && toggle {
+                self.cancel_hold();
+                return true;
+            }
+        }
+        let bound = interact
+            .bind()
+            .available_actions()
+            .into_iter()
+            .map(|action| action.bind().input_action())
+            .find(|input| *input != primary && pressed(input));
+        let Some(input) = bound else {
             return false;
         };
-        interact.emit_signal(InteractionComponent::SIGNAL_INTERACT, &[]) == Error::OK
+        self.try_action(input);
+        true
+    }
+
+    /// The currently selected action, or None for components without named actions
+    fn current_action(&self) -> Option<Gd<InteractionAction>> {
+        let interact = self.current_interaction()?;
+        let actions = interact.bind().available_actions();
+        actions.get(self.interaction().selected_action).cloned()
+    }
+
+    /// Advances any hold interaction in progress, triggering it once complete
+    fn process_hold(&mut self, delta: f32) {
+        let Some(elapsed) = self.interaction().hold_elapsed else {
+            return;
+        };
+        let Some(mut interact) = self.current_interaction() else {
+            self.cancel_hold();
+            return;
+        };
+        let elapsed = elapsed + delta;
+        let duration = interact.bind().hold_duration;
+        let progress = if duration > 0.0 {
+            (elapsed / duration).min(1.0)
+        } else {
+            1.0
+        };
+        self.interaction_mut().hold_elapsed = Some(elapsed);
+        self.reentrant(|| {
+            interact.emit_signal(
+                InteractionComponent::SIGNAL_HOLD_PROGRESS,
+                &[progress.to_variant()],
+            )
+        });
+        self.emit("interaction_progress", &[progress.to_variant()]);
+        // A progress handler may have cancelled the hold or changed the target
+        if self.interaction().hold_elapsed.is_none()
+            || self.current_interaction().as_ref() != Some(&interact)
+        {
+            return;
+        }
+        if progress >= 1.0 {
+            self.interaction_mut().hold_elapsed = None;
+            let action = self.interaction_mut().hold_action.take();
+            let interactor = self.base().clone().upcast::<Node3D>();
+            self.reentrant(|| InteractionComponent::perform(interact, action, interactor));
+        }
+    }
+
+    /// Interacts with the current target, or starts the hold for targets with a `hold_duration`
+    ///
+    /// Requirements are checked before anything happens. Refusals are also emitted through `interaction_refused`
+    fn try_interact(&mut self) -> Gd<InteractionResult> {
+        let action = self.current_action();
+        let result = self.check_interact(action);
+        if result.bind().is_success() {
+            return result;
+        }
+        self.emit("interaction_refused", &[result.to_variant()]);
+        result
+    }
+
+    /// Performs whichever of the target's actions is bound to the given input action
+    fn try_action(&mut self, input_action: StringName) -> Gd<InteractionResult> {
+        let action = self.current_interaction().and_then(|interact| {
+            interact
+                .bind()
+                .available_actions()
+                .into_iter()
+                .find(|action| action.bind().input_action() == input_action)
+        });
+        let result = match action {
+            Some(action) => self.check_interact(Some(action)),
+            None => InteractionResult::of(InteractionStatus::NoAction, GString::new()),
+        };
+        if result.bind().is_success() {
+            return result;
+        }
+        self.emit("interaction_refused", &[result.to_variant()]);
+        result
+    }
+
+    fn check_interact(&mut self, action: Option<Gd<InteractionAction>>) -> Gd<InteractionResult> {
+        let Some(interact) = self.current_interaction() else {
+            return InteractionResult::of(InteractionStatus::NoTarget, GString::new());
+        };
+        if !interact.bind().enabled {
+            return InteractionResult::of(InteractionStatus::Disabled, GString::new());
+        }
+        let interactor = self.base().clone().upcast::<Node3D>();
+        let requirements = interact.bind().requirements.clone();
+        let refused_by = self.reentrant(|| {
+            requirements.iter_shared().find(|requirement| {
+                !InteractionRequirement::is_met(
+                    requirement.clone(),
+                    interactor.clone(),
+                    interact.clone(),
+                )
+            })
+        });
+        if let Some(requirement) = refused_by {
+            return InteractionResult::refused_by(requirement);
+        }
+        if interact.bind().hold_duration > 0.0 {
+            let state = self.interaction_mut();
+            if state.hold_elapsed.is_none() {
+                state.hold_elapsed = Some(0.0);
+                state.hold_action = action;
+            }
+            return InteractionResult::of(InteractionStatus::HoldStarted, GString::new());
+        }
+        self.reentrant(|| InteractionComponent::perform(interact, action, interactor));
+        InteractionResult::of(InteractionStatus::Success, GString::new())
+    }
+
+    /// Stops any hold interaction in progress without triggering it
+    fn cancel_hold(&mut self) {
+        self.interaction_mut().hold_action = None;
+        if self.interaction_mut().hold_elapsed.take().is_none() {
+            return;
+        }
+        if let Some(mut interact) = self.interaction().component() {
+            self.reentrant(|| {
+                interact.emit_signal(InteractionComponent::SIGNAL_HOLD_CANCELLED, &[])
+            });
+        }
+        self.emit("interaction_cancelled", &[]);
+    }
+
+    /// The targeted component, or None if the target has since been freed or left the tree
+    fn current_interaction(&self) -> Option<Gd<InteractionComponent>> {
+        let state = self.interaction();
+        if !state.is_valid() {
+            return None;
+        }
+        state.component()
+    }
+
+    fn current_prompt(&self) -> GString {
+        let selected = self.interaction().selected_action;
+        self.current_interaction()
+            .map(|interact| interact.bind().get_action_prompt(selected as i32))
+            .unwrap_or_default()
     }
 }
 
 #[derive(Debug, GodotClass)]
 #[class(base=Node3D, init)]
 /// A component for creating an interactable object
-struct InteractionComponent {
+pub struct InteractionComponent {
+    #[export]
+    /// What interacting does, e.g. "open". Used to build the prompt shown to the player
+    prompt_text: GString,
+    #[export]
+    #[init(val = "interact".into())]
+    /// The input action used to trigger this interaction
+    action_name: StringName,
+    #[export]
+    /// An optional icon for HUDs to display alongside the prompt
+    icon: Option<Gd<Texture2D>>,
+    #[export]
+    /// How long, in seconds, the interaction must be held. Zero means instant
+    hold_duration: f32,
+    #[export]
+    #[init(val = true)]
+    /// Disabled interactables cannot be selected or interacted with
+    enabled: bool,
+    #[export(flags = (Channel1 = 1, Channel2 = 2, Channel3 = 4, Channel4 = 8))]
+    #[init(val = 1)]
+    /// Only interactors sharing at least one channel can target this
+    interaction_channels: u32,
+    #[export]
+    /// Free-form tags interactors can filter on
+    tags: PackedStringArray,
+    #[export]
+    /// Conditions checked when interacting. The first failing requirement refuses the interaction
+    requirements: Array<Gd<InteractionRequirement>>,
+    /// Whoever interacted with this most recently
+    interactor: Option<InstanceId>,
+    #[export]
+    /// Triggered through the [EventDepot] whenever this is selected
+    select_event: Option<Gd<EventArgs>>,
+    #[export]
+    /// Triggered through the [EventDepot] whenever this is deselected
+    deselect_event: Option<Gd<EventArgs>>,
+    #[export]
+    /// Triggered through the [EventDepot] whenever this is interacted with
+    interact_event: Option<Gd<EventArgs>>,
+    #[export]
+    /// Named verbs this interactable supports. When empty the component acts as a single action using `prompt_text` and `action_name`
+    actions: Array<Gd<InteractionAction>>,
     base: Base<Node3D>,
 }
 
@@ -97,6 +540,9 @@ impl InteractionComponent {
     pub const SIGNAL_SELECT: &'static str = "on_select";
     pub const SIGNAL_DESELECT: &'static str = "on_deselect";
     pub const SIGNAL_INTERACT: &'static str = "on_interact";
+    pub const SIGNAL_HOLD_PROGRESS: &'static str = "on_hold_progress";
+    pub const SIGNAL_HOLD_CANCELLED: &'static str = "on_hold_cancelled";
+    pub const SIGNAL_ACTION: &'static str = "on_action";
 
     #[signal]
     /// Emitted when the player is first able to interact but hasn't
@@ -109,4 +555,134 @@ impl InteractionComponent {
     #[signal]
     /// Emitted when the player has actually intentionally interacted with this object
     pub fn on_interact() {}
+
+    #[signal]
+    /// Emitted alongside `on_interact` when one of the named actions was performed
+    pub fn on_action(verb: GString) {}
+
+    #[signal]
+    /// Emitted every physics tick while the player holds the interaction, with progress from 0 to 1
+    pub fn on_hold_progress(progress: f32) {}
+
+    #[signal]
+    /// Emitted when the player stops holding before the interaction completed
+    pub fn on_hold_cancelled() {}
+
+    #[func]
+    /// The full prompt for this interaction, e.g. "Press E to open"
+    ///
+    /// Falls back to just the prompt text if the action has no bindings
+    pub fn get_prompt(&self) -> GString {
+        self.get_action_prompt(0)
+    }
+
+    #[func]
+    /// The full prompt for one of the available actions. Components without named actions always use their own prompt
+    pub fn get_action_prompt(&self, index: i32) -> GString {
+        if self.actions.is_empty() {
+            return format_prompt(&self.action_name, &self.prompt_text);
+        }
+        let actions = self.available_actions();
+        let Some(action) = usize::try_from(index).ok().and_then(|i| actions.get(i)) else {
+            return GString::new();
+        };
+        let action = action.bind();
+        format_prompt(&action.input_action(), &action.verb())
+    }
+
+    #[func]
+    /// The verbs of every enabled action, in order
+    pub fn get_verbs(&self) -> PackedStringArray {
+        if self.actions.is_empty() {
+            if self.prompt_text.is_empty() {
+                return PackedStringArray::new();
+            }
+            return PackedStringArray::from(std::slice::from_ref(&self.prompt_text));
+        }
+        self.available_actions()
+            .iter()
+            .map(|action| action.bind().verb())
+            .collect()
+    }
+
+    /// The enabled named actions, in order
+    pub fn available_actions(&self) -> Vec<Gd<InteractionAction>> {
+        self.actions
+            .iter_shared()
+            .filter(|action| action.bind().is_enabled())
+            .collect()
+    }
+
+    /// Emits one of the select/deselect/interact signals, also triggering its matching event if one is set
+    pub fn notify(mut this: Gd<Self>, signal: &str) {
+        this.emit_signal(signal, &[]);
+        let event = {
+            let interact = this.bind();
+            match signal {
+                Self::SIGNAL_SELECT => interact.select_event.clone(),
+                Self::SIGNAL_DESELECT => interact.deselect_event.clone(),
+                Self::SIGNAL_INTERACT => interact.interact_event.clone(),
+                _ => None,
+            }
+        };
+        let (Some(event), Some(depot)) = (event, EventDepot::singleton()) else {
+            return;
+        };
+        depot.bind().trigger(event);
+    }
+
+    #[func]
+    /// The interactor that most recently interacted with this, if it still exists. Useful from `on_interact` handlers
+    pub fn get_interactor(&self) -> Option<Gd<Node3D>> {
+        Gd::try_from_instance_id(self.interactor?).ok()
+    }
+
+    /// Triggers the interaction on behalf of the interactor, running the given named action if there is one
+    pub fn perform(
+        mut this: Gd<Self>,
+        action: Option<Gd<InteractionAction>>,
+        interactor: Gd<Node3D>,
+    ) {
+        this.bind_mut().interactor = Some(interactor.instance_id());
+        Self::notify(this.clone(), Self::SIGNAL_INTERACT);
+        let Some(action) = action else {
+            return;
+        };
+        let (verb, method) = {
+            let action = action.bind();
+            (action.verb(), action.method())
+        };
+        this.emit_signal(Self::SIGNAL_ACTION, &[verb.to_variant()]);
+        if method.is_empty() {
+            return;
+        }
+        if let Some(mut interactable) = this.get_parent() {
+            interactable.call(&method, &[]);
+        }
+    }
+}
+
+/// Builds a prompt like "Press E to open" from an input action and verb
+fn format_prompt(action: &StringName, text: &GString) -> GString {
+    let Some(key) = action_hint(action) else {
+        return text.clone();
+    };
+    if text.is_empty() {
+        return format!("Press {}", key).into();
+    }
+    format!("Press {} to {}", key, text).into()
+}
+
+/// A human readable name for the first input bound to an action
+fn action_hint(action: &StringName) -> Option<GString> {
+    let mut input_map = InputMap::singleton();
+    if !input_map.has_action(action) {
+        return None;
+    }
+    let event = input_map.action_get_events(action).front()?;
+    match event.clone().try_cast::<InputEventKey>() {
+        Ok(key) if key.get_physical_keycode() != Key::NONE => Some(key.as_text_physical_keycode()),
+        Ok(key) => Some(key.as_text_keycode()),
+        Err(_) => Some(event.as_text()),
+    }
 }
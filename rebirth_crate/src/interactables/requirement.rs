@@ -0,0 +1,195 @@
+use godot::{
+    classes::{CollisionObject3D, PhysicsRayQueryParameters3D},
+    prelude::*,
+};
+
+use crate::inventory::Inventory;
+
+use super::InteractionComponent;
+
+#[derive(GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[godot(via=i32)]
+/// What an [InteractionRequirement] checks for
+pub enum RequirementKind {
+    #[default]
+    /// The [Inventory] the interactor belongs to must hold at least one `item`
+    RequiredItem,
+    /// The interactable's `state_property` must equal `state_value`
+    RequiredState,
+    /// The interactor must be within `max_distance` of the interaction component
+    Distance,
+    /// Nothing may be blocking a ray from the interactor to the interaction component
+    LineOfSight,
+    /// Always passes unless a script overrides `_is_met`
+    Custom,
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, init, tool)]
+/// A condition that must pass before an [InteractionComponent] can be interacted with
+///
+/// Extend this in GDScript and override `_is_met` for anything the built in kinds can't express
+pub struct InteractionRequirement {
+    #[export]
+    kind: RequirementKind,
+    #[export]
+    /// Shown to the player when this requirement fails, e.g. "Locked". A generic message is used when empty
+    message: GString,
+    #[export]
+    /// The item id for [RequirementKind::RequiredItem]
+    item: StringName,
+    #[export]
+    /// The property on the interactable for [RequirementKind::RequiredState]
+    state_property: StringName,
+    #[export]
+    /// The value the property must equal for [RequirementKind::RequiredState]
+    state_value: Variant,
+    #[export]
+    #[init(val = 2.0)]
+    /// The maximum distance for [RequirementKind::Distance]
+    max_distance: f32,
+    base: Base<Resource>,
+}
+
+#[godot_api]
+impl InteractionRequirement {
+    #[func(virtual, gd_self)]
+    /// Whether the interactor is currently allowed to interact with the component
+    pub fn is_met(
+        this: Gd<Self>,
+        interactor: Gd<Node3D>,
+        interaction: Gd<InteractionComponent>,
+    ) -> bool {
+        let req = this.bind();
+        let Some(interactable) = interaction.get_parent() else {
+            return false;
+        };
+        match req.kind {
+            RequirementKind::RequiredItem => Inventory::find_for(&interactor.clone().upcast())
+                .is_some_and(|inventory| inventory.bind().has_item(req.item.clone())),
+            RequirementKind::RequiredState => {
+                interactable.get(&req.state_property) == req.state_value
+            }
+            RequirementKind::Distance => {
+                interactor
+                    .get_global_position()
+                    .distance_to(interaction.get_global_position())
+                    <= req.max_distance
+            }
+            RequirementKind::LineOfSight => has_line_of_sight(&interactor, &interactable),
+            RequirementKind::Custom => true,
+        }
+    }
+
+    #[func]
+    /// The message to show when this requirement fails
+    pub fn get_refusal_message(&self) -> GString {
+        if !self.message.is_empty() {
+            return self.message.clone();
+        }
+        match self.kind {
+            RequirementKind::RequiredItem => format!("Requires {}", self.item).into(),
+            RequirementKind::RequiredState => "Can't do that right now".into(),
+            RequirementKind::Distance => "Too far away".into(),
+            RequirementKind::LineOfSight => "Can't reach that".into(),
+            RequirementKind::Custom => "Can't do that".into(),
+        }
+    }
+}
+
+/// Casts a ray from the interactor to the interactable, ignoring the body the interactor is attached to
+fn has_line_of_sight(interactor: &Gd<Node3D>, interactable: &Gd<Node>) -> bool {
+    let Ok(target) = interactable.clone().try_cast::<Node3D>() else {
+        return false;
+    };
+    let Some(mut space) = interactor
+        .get_world_3d()
+        .and_then(|mut world| world.get_direct_space_state())
+    else {
+        return false;
+    };
+    let Some(mut query) = PhysicsRayQueryParameters3D::create(
+        interactor.get_global_position(),
+        target.get_global_position(),
+    ) else {
+        return false;
+    };
+    let mut holder = interactor.get_parent();
+    while let Some(node) = holder {
+        if let Ok(body) = node.clone().try_cast::<CollisionObject3D>() {
+            query.set_exclude(&array![body.get_rid()]);
+            break;
+        }
+        holder = node.get_parent();
+    }
+    let hit = space.intersect_ray(&query);
+    match hit.get("collider") {
+        None => true,
+        Some(collider) => collider == target.to_variant(),
+    }
+}
+
+#[derive(GodotConvert, Var, Export, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[godot(via=i32)]
+/// The outcome of an interaction attempt
+pub enum InteractionStatus {
+    #[default]
+    /// The interaction was triggered
+    Success,
+    /// The interaction requires holding and the hold has started
+    HoldStarted,
+    /// Nothing is currently targeted
+    NoTarget,
+    /// The target has no action bound to the requested input
+    NoAction,
+    /// The targeted interaction component is disabled
+    Disabled,
+    /// One of the component's requirements was not met
+    RequirementFailed,
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=RefCounted, init)]
+/// Describes what happened when an interactor tried to interact, so HUDs can explain refusals
+pub struct InteractionResult {
+    #[var(get)]
+    status: InteractionStatus,
+    #[var(get)]
+    /// A player facing explanation of why the interaction was refused. Empty on success
+    message: GString,
+    #[var(get)]
+    /// The requirement that refused the interaction, if any
+    requirement: Option<Gd<InteractionRequirement>>,
+    base: Base<RefCounted>,
+}
+
+#[godot_api]
+impl InteractionResult {
+    #[func]
+    /// Whether the interaction went through (or its hold started)
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self.status,
+            InteractionStatus::Success | InteractionStatus::HoldStarted
+        )
+    }
+
+    pub fn of(status: InteractionStatus, message: GString) -> Gd<Self> {
+        Gd::from_init_fn(|base| Self {
+            status,
+            message,
+            requirement: None,
+            base,
+        })
+    }
+
+    pub fn refused_by(requirement: Gd<InteractionRequirement>) -> Gd<Self> {
+        let message = requirement.bind().get_refusal_message();
+        Gd::from_init_fn(|base| Self {
+            status: InteractionStatus::RequirementFailed,
+            message,
+            requirement: Some(requirement),
+            base,
+        })
+    }
+}
@@ -0,0 +1,132 @@
+use godot::{
+    classes::{IShapeCast3D, InputEvent, ShapeCast3D, SphereShape3D},
+    prelude::*,
+};
+
+use super::{InteractionComponent, InteractionResult, InteractionTarget, RustyInteractor};
+
+#[derive(Debug, GodotClass)]
+#[class(base=ShapeCast3D, init)]
+/// An interactor that sweeps a shape and picks the best interactable within a view cone
+///
+/// Candidates are scored by how far off-center and how far away they are, which makes small items much easier to target than with a plain [Interactor]
+struct ShapeInteractor {
+    #[export]
+    #[init(val = 30.0)]
+    /// Interactables further than this from the forward (-Z) axis are ignored
+    max_angle_degrees: f32,
+    #[export]
+    #[init(val = 3.0)]
+    /// Distance used to normalize the distance score. Interactables further than this are ignored
+    max_distance: f32,
+    #[export]
+    #[init(val = 1.0)]
+    /// How much being off-center counts against a candidate
+    angle_weight: f32,
+    #[export]
+    #[init(val = 0.5)]
+    /// How much being far away counts against a candidate
+    distance_weight: f32,
+
+    #[export(flags = (Channel1 = 1, Channel2 = 2, Channel3 = 4, Channel4 = 8))]
+    #[init(val = 1)]
+    /// Only interactables sharing at least one channel can be targeted
+    interaction_channels: u32,
+    #[export]
+    /// If not empty, only interactables with at least one of these tags can be targeted
+    accepted_tags: PackedStringArray,
+
+    interaction: InteractionTarget,
+    base: Base<ShapeCast3D>,
+}
+
+impl RustyInteractor<ShapeCast3D> for ShapeInteractor {
+    fn interaction(&self) -> &InteractionTarget {
+        &self.interaction
+    }
+
+    fn interaction_mut(&mut self) -> &mut InteractionTarget {
+        &mut self.interaction
+    }
+
+    fn interaction_channels(&self) -> u32 {
+        self.interaction_channels
+    }
+
+    fn accepted_tags(&self) -> PackedStringArray {
+        self.accepted_tags.clone()
+    }
+}
+
+#[godot_api]
+impl IShapeCast3D for ShapeInteractor {
+    fn ready(&mut self) {
+        if self.base().get_shape().is_none() {
+            let mut sphere = SphereShape3D::new_gd();
+            sphere.set_radius(0.5);
+            self.base_mut().set_shape(&sphere);
+        }
+        // The scoring looks down -Z, so sweep that way unless another direction was authored
+        if self.base().get_target_position() == Self::DEFAULT_TARGET_POSITION {
+            let target = Vector3::new(0.0, 0.0, -self.max_distance);
+            self.base_mut().set_target_position(target);
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        let best = self.find_best_candidate();
+        self.set_target(best);
+        self.process_hold(delta as f32);
+    }
+
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        self.handle_action_input(&event);
+    }
+}
+
+impl ShapeInteractor {
+    /// [ShapeCast3D]'s own default, which sweeps straight down
+    const DEFAULT_TARGET_POSITION: Vector3 = Vector3::new(0.0, -1.0, 0.0);
+
+    /// Scores every interactable the shape currently overlaps and returns the lowest scoring one
+    fn find_best_candidate(&self) -> Option<Gd<Node3D>> {
+        let origin = self.base().get_global_transform();
+        let forward = -origin.basis.col_c().normalized();
+        let max_angle = self.max_angle_degrees.to_radians();
+
+        let mut best: Option<(f32, Gd<Node3D>)> = None;
+        for index in 0..self.base().get_collision_count() {
+            let Some((node, interact)) = self
+                .base()
+                .get_collider(index)
+                .and_then(|collider| self.interactable_of(collider))
+            else {
+                continue;
+            };
+            let offset = interact.get_global_position() - origin.origin;
+            let distance = offset.length();
+            if distance > self.max_distance {
+                continue;
+            }
+            let angle = if distance > f32::EPSILON {
+                forward.angle_to(offset / distance)
+            } else {
+                0.0
+            };
+            if angle > max_angle {
+                continue;
+            }
+            let score = self.angle_weight * (angle / max_angle.max(f32::EPSILON))
+                + self.distance_weight * (distance / self.max_distance.max(f32::EPSILON));
+            if best
+                .as_ref()
+                .is_none_or(|(best_score, _)| score < *best_score)
+            {
+                best = Some((score, node));
+            }
+        }
+        best.map(|(_, node)| node)
+    }
+}
+
+interactor_api!(ShapeInteractor);
@@ -0,0 +1,303 @@
+use godot::{classes::Texture2D, obj::WithBaseField, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    component_utils::{RustyComponent, RustyComponents},
+    versioning::{Migration, VersionedFormat},
+};
+
+mod pickup;
+
+#[derive(Debug, GodotClass)]
+#[class(base=Resource, init, tool)]
+/// The static description of an item that can be held in an [Inventory]
+pub struct ItemDefinition {
+    #[export]
+    /// A unique id for this item. Stacks, requirements and lookups all use this
+    id: StringName,
+    #[export]
+    /// The player facing name
+    name: GString,
+    #[export]
+    icon: Option<Gd<Texture2D>>,
+    #[export]
+    #[init(val = 1)]
+    /// How many of this item fit in a single inventory slot
+    stack_size: i32,
+    #[export]
+    /// The weight of a single one of this item
+    weight: f32,
+    base: Base<Resource>,
+}
+
+/// Migrations for [InventoryData] on its own, oldest first. Inventories inside saves are migrated as part of the save instead
+const INVENTORY_MIGRATIONS: &[Migration] = &[];
+pub const INVENTORY_FORMAT: VersionedFormat = VersionedFormat::new(INVENTORY_MIGRATIONS);
+
+/// A single inventory slot
+#[derive(Debug, Clone)]
+struct ItemStack {
+    item: Gd<ItemDefinition>,
+    count: i32,
+}
+
+/// The serialized form of an [Inventory]. Items are stored by resource path so they can be loaded back, which means only items saved as their own resource file can be saved
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct InventoryData {
+    pub stacks: Vec<ItemStackData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemStackData {
+    pub item: String,
+    pub count: i32,
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Node, init)]
+/// A component holding stacks of [ItemDefinition]s
+///
+/// Attach this to the player (or a chest, etc) and interactors belonging to it can pick up items through a [PickupComponent](pickup::PickupComponent)
+pub struct Inventory {
+    #[export]
+    /// The maximum number of stacks. Zero means unlimited
+    max_slots: i32,
+    #[export]
+    /// The maximum total weight. Zero means unlimited
+    max_weight: f32,
+    stacks: Vec<ItemStack>,
+    base: Base<Node>,
+}
+
+impl RustyComponent<Node> for Inventory {}
+
+#[godot_api]
+impl INode for Inventory {
+    fn ready(&mut self) {
+        self.on_ready();
+    }
+}
+
+#[godot_api]
+impl Inventory {
+    pub const SIGNAL_ITEM_ADDED: &'static str = "item_added";
+    pub const SIGNAL_ITEM_REMOVED: &'static str = "item_removed";
+    pub const SIGNAL_CHANGED: &'static str = "inventory_changed";
+
+    #[signal]
+    /// Emitted after items were added, with the amount actually added
+    pub fn item_added(item: Gd<ItemDefinition>, count: i32) {}
+
+    #[signal]
+    /// Emitted after items were removed, with the amount actually removed
+    pub fn item_removed(item: Gd<ItemDefinition>, count: i32) {}
+
+    #[signal]
+    /// Emitted after any change to the contents
+    pub fn inventory_changed() {}
+
+    #[func]
+    /// Adds up to `count` of the item, filling existing stacks first. Returns how many were actually added
+    pub fn add_item(&mut self, item: Gd<ItemDefinition>, count: i32) -> i32 {
+        let (id, stack_size, weight) = {
+            let def = item.bind();
+            (def.id.clone(), def.stack_size.max(1), def.weight)
+        };
+        let mut remaining = count.max(0);
+        if self.max_weight > 0.0 && weight > 0.0 {
+            let room = ((self.max_weight - self.get_total_weight()) / weight).floor() as i32;
+            remaining = remaining.min(room.max(0));
+        }
+        let requested = remaining;
+
+        for stack in self.stacks.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            if stack.item.bind().id != id {
+                continue;
+            }
+            let moved = (stack_size - stack.count).clamp(0, remaining);
+            stack.count += moved;
+            remaining -= moved;
+        }
+        while remaining > 0 && (self.max_slots <= 0 || (self.stacks.len() as i32) < self.max_slots)
+        {
+            let moved = stack_size.min(remaining);
+            self.stacks.push(ItemStack {
+                item: item.clone(),
+                count: moved,
+            });
+            remaining -= moved;
+        }
+
+        let added = requested - remaining;
+        if added > 0 {
+            self.base_mut().emit_signal(
+                Self::SIGNAL_ITEM_ADDED,
+                &[item.to_variant(), added.to_variant()],
+            );
+            self.base_mut().emit_signal(Self::SIGNAL_CHANGED, &[]);
+        }
+        added
+    }
+
+    #[func]
+    /// Removes up to `count` of the item with the given id, emptying the newest stacks first. Returns how many were actually removed
+    pub fn remove_item(&mut self, id: StringName, count: i32) -> i32 {
+        let mut remaining = count.max(0);
+        let mut removed_item = None;
+        for stack in self.stacks.iter_mut().rev() {
+            if remaining == 0 {
+                break;
+            }
+            if stack.item.bind().id != id {
+                continue;
+            }
+            let moved = stack.count.min(remaining);
+            stack.count -= moved;
+            remaining -= moved;
+            removed_item = Some(stack.item.clone());
+        }
+        self.stacks.retain(|stack| stack.count > 0);
+
+        let removed = count.max(0) - remaining;
+        if let Some(item) = removed_item {
+            self.base_mut().emit_signal(
+                Self::SIGNAL_ITEM_REMOVED,
+                &[item.to_variant(), removed.to_variant()],
+            );
+            self.base_mut().emit_signal(Self::SIGNAL_CHANGED, &[]);
+        }
+        removed
+    }
+
+    #[func]
+    /// Whether at least one of the item with the given id is held
+    pub fn has_item(&self, id: StringName) -> bool {
+        self.count_item(id) > 0
+    }
+
+    #[func]
+    /// The total amount held of the item with the given id, across all stacks
+    pub fn count_item(&self, id: StringName) -> i32 {
+        self.stacks
+            .iter()
+            .filter(|stack| stack.item.bind().id == id)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    #[func]
+    pub fn get_total_weight(&self) -> f32 {
+        self.stacks
+            .iter()
+            .map(|stack| stack.item.bind().weight * stack.count as f32)
+            .sum()
+    }
+
+    #[func]
+    /// Every stack as a dictionary of `item` and `count`, in slot order
+    pub fn get_stacks(&self) -> Array<Dictionary> {
+        self.stacks
+            .iter()
+            .map(|stack| dict! { "item": stack.item.clone(), "count": stack.count })
+            .collect()
+    }
+
+    #[func]
+    /// Removes every item
+    pub fn clear(&mut self) {
+        self.stacks.clear();
+        self.base_mut().emit_signal(Self::SIGNAL_CHANGED, &[]);
+    }
+
+    #[func]
+    /// Serializes the contents to a RON string
+    pub fn to_ron(&self) -> GString {
+        match INVENTORY_FORMAT.write(&self.save_data()) {
+            Ok(data) => data.into(),
+            Err(err) => {
+                godot_error!("Failed to serialize inventory: {}", err);
+                GString::new()
+            }
+        }
+    }
+
+    #[func]
+    /// Replaces the contents with those serialized by [Inventory::to_ron]. Returns false if the data couldn't be parsed
+    pub fn load_ron(&mut self, data: GString) -> bool {
+        match INVENTORY_FORMAT.read::<InventoryData>(&data.to_string()) {
+            Ok(parsed) => {
+                self.load_data(parsed);
+                true
+            }
+            Err(err) => {
+                godot_error!("Failed to parse inventory data: {}", err);
+                false
+            }
+        }
+    }
+
+    /// The contents in their serialized form. Items that weren't loaded from their own resource file can't be loaded back, so they are left out with a warning
+    pub fn save_data(&self) -> InventoryData {
+        InventoryData {
+            stacks: self
+                .stacks
+                .iter()
+                .filter_map(|stack| {
+                    let path = stack.item.get_path().to_string();
+                    if path.is_empty() || path.contains("::") {
+                        godot_warn!(
+                            "Not saving {} x{}: the item definition must be saved as its own resource file",
+                            stack.item.bind().id,
+                            stack.count
+                        );
+                        return None;
+                    }
+                    Some(ItemStackData {
+                        item: path,
+                        count: stack.count,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load_data(&mut self, data: InventoryData) {
+        self.stacks = data
+            .stacks
+            .into_iter()
+            .filter_map(|stack| {
+                let Some(item) = try_load::<ItemDefinition>(&stack.item).ok() else {
+                    godot_warn!("Skipping unknown inventory item {}", stack.item);
+                    return None;
+                };
+                let stack_size = item.bind().stack_size.max(1);
+                let count = stack.count.clamp(0, stack_size);
+                if count != stack.count {
+                    godot_warn!(
+                        "Clamping inventory stack of {} from {} to {}",
+                        stack.item,
+                        stack.count,
+                        count
+                    );
+                }
+                (count > 0).then_some(ItemStack { item, count })
+            })
+            .collect();
+        self.base_mut().emit_signal(Self::SIGNAL_CHANGED, &[]);
+    }
+
+    /// Finds the inventory a node belongs to, by looking for an [Inventory] component on it or any of its ancestors
+    pub fn find_for(node: &Gd<Node>) -> Option<Gd<Inventory>> {
+        let mut current = Some(node.clone());
+        while let Some(node) = current {
+            if let Some(inventory) = RustyComponents::get_component::<Inventory>(&node) {
+                return Some(inventory);
+            }
+            current = node.get_parent();
+        }
+        None
+    }
+}
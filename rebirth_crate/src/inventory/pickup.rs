@@ -0,0 +1,82 @@
+use godot::{obj::WithBaseField, prelude::*};
+
+use crate::{
+    component_utils::{RustyComponent, RustyComponents},
+    interactables::InteractionComponent,
+};
+
+use super::{Inventory, ItemDefinition};
+
+#[derive(Debug, GodotClass)]
+#[class(base=Node, init)]
+/// Moves an item into the interacting player's [Inventory] when the sibling [InteractionComponent] is interacted with
+pub struct PickupComponent {
+    #[export]
+    item: Option<Gd<ItemDefinition>>,
+    #[export]
+    #[init(val = 1)]
+    count: i32,
+    #[export]
+    #[init(val = true)]
+    /// Frees the interactable once everything has been picked up
+    free_on_pickup: bool,
+    base: Base<Node>,
+}
+
+impl RustyComponent<Node> for PickupComponent {}
+
+#[godot_api]
+impl INode for PickupComponent {
+    fn ready(&mut self) {
+        self.on_ready();
+        let Some(parent) = self.base().get_parent() else {
+            return;
+        };
+        let Some(mut interact) = RustyComponents::get_component::<InteractionComponent>(&parent)
+        else {
+            godot_warn!(
+                "PickupComponent on {} has no sibling InteractionComponent",
+                parent.get_name()
+            );
+            return;
+        };
+        interact.connect(
+            InteractionComponent::SIGNAL_INTERACT,
+            &Callable::from_object_method(&self.to_gd(), "pick_up"),
+        );
+    }
+}
+
+#[godot_api]
+impl PickupComponent {
+    #[signal]
+    /// Emitted after some or all of the item was moved into an inventory
+    fn picked_up(inventory: Gd<Inventory>, count: i32) {}
+
+    #[func]
+    /// Moves as much of the item as fits into the inventory of whoever last interacted with the sibling [InteractionComponent]
+    fn pick_up(&mut self) {
+        let Some(item) = self.item.clone() else {
+            return;
+        };
+        let Some(parent) = self.base().get_parent() else {
+            return;
+        };
+        let Some(mut inventory) = RustyComponents::get_component::<InteractionComponent>(&parent)
+            .and_then(|interact| interact.bind().get_interactor())
+            .and_then(|interactor| Inventory::find_for(&interactor.upcast()))
+        else {
+            return;
+        };
+        let added = inventory.bind_mut().add_item(item, self.count);
+        if added <= 0 {
+            return;
+        }
+        self.count -= added;
+        self.base_mut()
+            .emit_signal("picked_up", &[inventory.to_variant(), added.to_variant()]);
+        if self.count <= 0 && self.free_on_pickup {
+            parent.clone().queue_free();
+        }
+    }
+}
@@ -7,7 +7,10 @@ mod event_depot;
 mod game_settings;
 mod godot_impls;
 mod interactables;
+mod inventory;
+mod save_system;
 mod state_machine;
+mod versioning;
 struct RebirthCrate;
 
 #[gdextension]
@@ -17,6 +20,7 @@ unsafe impl ExtensionLibrary for RebirthCrate {
             return;
         }
         event_depot::register();
+        save_system::register();
         game_settings::register();
         component_utils::register();
     }
@@ -26,6 +30,7 @@ unsafe impl ExtensionLibrary for RebirthCrate {
             return;
         }
         event_depot::unregister();
+        save_system::unregister();
         game_settings::unregister();
         component_utils::unregister();
     }
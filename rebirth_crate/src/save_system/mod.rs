@@ -0,0 +1,412 @@
+use std::collections::BTreeMap;
+
+use godot::{
+    classes::{file_access::ModeFlags, object::ConnectFlags, DirAccess, Engine, FileAccess, Time},
+    global::Error,
+    prelude::*,
+};
+use persistent::{NodeSaveData, Persistent};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    component_utils::RustyComponents,
+    engine_helpers::{engine, CrateSingleton},
+    versioning::{Migration, VersionError, VersionedFormat},
+};
+
+mod persistent;
+
+pub fn register() {
+    Engine::singleton().register_singleton(SaveSystem::ID, &SaveSystem::new_alloc());
+}
+
+pub fn unregister() {
+    Engine::singleton().unregister_singleton(SaveSystem::ID);
+}
+
+/// Migrations for [SaveDocument], oldest first. Add a migration here whenever the save structs change
+const SAVE_MIGRATIONS: &[Migration] = &[];
+pub const SAVE_FORMAT: VersionedFormat = VersionedFormat::new(SAVE_MIGRATIONS);
+
+/// Information about a save that can be shown without loading it
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    pub slot: String,
+    /// Seconds played across all sessions
+    pub playtime: f64,
+    /// Unix time the save was written
+    pub timestamp: i64,
+    /// The scene file that was current when saving. Loading changes back to it
+    pub scene: String,
+    /// A player facing name for where the save was made
+    pub location: String,
+}
+
+/// A whole save file, holding the data of every [Persistent] node keyed by its save id
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SaveDocument {
+    pub meta: SaveMetadata,
+    pub nodes: BTreeMap<String, NodeSaveData>,
+}
+
+impl SaveDocument {
+    /// Parses a save file's contents, migrating older saves to the current format
+    pub fn parse(text: &str) -> Result<Self, VersionError> {
+        SAVE_FORMAT.read(text)
+    }
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Object)]
+/// Saves and loads the state of every [Persistent] node in the scene tree as RON files in `user://saves/`
+pub struct SaveSystem {
+    #[var]
+    /// A player facing name for the current location, stored in the save metadata. Defaults to the current scene's name when empty
+    location_name: GString,
+    /// Playtime carried over from the loaded save
+    playtime_offset: f64,
+    /// Engine ticks when the playtime was last reset
+    session_start_msec: u64,
+    /// Data from the last load, waiting for its nodes to be instantiated
+    pending: BTreeMap<String, NodeSaveData>,
+    /// The slot and scene file of a load waiting for its scene to be added to the tree
+    loading: Option<(GString, GString)>,
+    base: Base<Object>,
+}
+
+#[godot_api]
+impl IObject for SaveSystem {
+    fn init(base: Base<Object>) -> Self {
+        Self {
+            location_name: GString::new(),
+            playtime_offset: 0.0,
+            session_start_msec: Time::singleton().get_ticks_msec(),
+            pending: BTreeMap::new(),
+            loading: None,
+            base,
+        }
+    }
+}
+
+impl CrateSingleton for SaveSystem {
+    const ID: &'static str = "SaveSystem";
+}
+
+#[godot_api]
+impl SaveSystem {
+    const SAVE_DIR: &'static str = "user://saves";
+
+    #[signal]
+    /// Emitted after a save file was written
+    fn game_saved(slot: GString) {}
+
+    #[signal]
+    /// Emitted after a save file was read and applied to the nodes currently in the tree
+    fn game_loaded(slot: GString) {}
+
+    #[func]
+    /// Collects every [Persistent] node in the tree and writes them to the given slot. Returns false if writing failed
+    pub fn save_game(&mut self, slot: GString) -> bool {
+        self.pending.clear();
+        let document = SaveDocument {
+            meta: self.make_metadata(&slot),
+            nodes: RustyComponents::get_all_components::<Persistent>()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|persistent| persistent.bind().collect())
+                .collect(),
+        };
+        let data = match SAVE_FORMAT.write(&document) {
+            Ok(data) => data,
+            Err(err) => {
+                godot_error!("Failed to serialize save {}: {}", slot, err);
+                return false;
+            }
+        };
+        if DirAccess::make_dir_recursive_absolute(Self::SAVE_DIR) != Error::OK {
+            godot_error!("Failed to create save directory {}", Self::SAVE_DIR);
+            return false;
+        }
+        let Some(path) = Self::slot_path(&slot) else {
+            return false;
+        };
+        let Some(mut file) = FileAccess::open(&path, ModeFlags::WRITE) else {
+            godot_error!(
+                "Failed to open {} for writing: {}",
+                path,
+                FileAccess::get_open_error().godot_name()
+            );
+            return false;
+        };
+        file.store_string(&data);
+        file.close();
+        self.base_mut()
+            .emit_signal("game_saved", &[slot.to_variant()]);
+        true
+    }
+
+    #[func]
+    /// Reads the given slot and restores its state
+    ///
+    /// If the save was made in a different scene, that scene is loaded first and each [Persistent] node restores itself once it is instantiated. `game_loaded` is then emitted once the new scene is ready
+    ///
+    /// Saved data without a matching [Persistent] node is dropped with a warning once loading finishes
+    pub fn load_game(&mut self, slot: GString) -> bool {
+        let Some(document) = Self::read_slot(&slot) else {
+            return false;
+        };
+        self.playtime_offset = document.meta.playtime;
+        self.session_start_msec = Time::singleton().get_ticks_msec();
+        self.location_name = document.meta.location.clone().into();
+        self.pending = document.nodes;
+
+        let Some(mut tree) = engine::get_scene_tree() else {
+            return false;
+        };
+        let current_scene = tree
+            .get_current_scene()
+            .map(|scene| scene.get_scene_file_path().to_string())
+            .unwrap_or_default();
+        if !document.meta.scene.is_empty() && document.meta.scene != current_scene {
+            let err = tree.change_scene_to_file(&document.meta.scene);
+            if err != Error::OK {
+                godot_error!(
+                    "Failed to change to saved scene {}: {}",
+                    document.meta.scene,
+                    err.godot_name()
+                );
+                return false;
+            }
+            self.wait_for_scene(slot, document.meta.scene.into());
+            return true;
+        }
+        for mut persistent in
+            RustyComponents::get_all_components::<Persistent>().unwrap_or_default()
+        {
+            let id = persistent.bind().resolve_save_id();
+            if let Some(data) = self.pending.remove(&id.to_string()) {
+                persistent.bind_mut().apply(data);
+            }
+        }
+        self.finish_load(slot);
+        true
+    }
+
+    #[func]
+    /// Connected to the root's `child_entered_tree` while [SaveSystem::load_game] waits for the saved scene. Finishes loading once that scene is ready
+    fn on_scene_entered(&mut self, mut node: Gd<Node>) {
+        let Some((slot, scene)) = self.loading.clone() else {
+            return;
+        };
+        if node.get_scene_file_path() != scene {
+            return;
+        }
+        self.stop_waiting_for_scene();
+        let finish =
+            Callable::from_object_method(&self.to_gd(), "finish_load").bindv(&varray![slot]);
+        node.connect_ex("ready", &finish)
+            .flags(ConnectFlags::ONE_SHOT.ord() as u32)
+            .done();
+    }
+
+    #[func]
+    /// Emits `game_loaded`. Every [Persistent] node of the loaded scene has restored itself by now, so whatever data is left has no node to go to
+    fn finish_load(&mut self, slot: GString) {
+        if !self.pending.is_empty() {
+            godot_warn!(
+                "No Persistent nodes for saved ids: {}",
+                self.pending.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+            self.pending.clear();
+        }
+        self.base_mut()
+            .emit_signal("game_loaded", &[slot.to_variant()]);
+    }
+
+    #[func]
+    pub fn has_save(&self, slot: GString) -> bool {
+        Self::slot_path(&slot).is_some_and(|path| FileAccess::file_exists(&path))
+    }
+
+    #[func]
+    pub fn delete_save(&mut self, slot: GString) -> bool {
+        Self::slot_path(&slot).is_some_and(|path| DirAccess::remove_absolute(&path) == Error::OK)
+    }
+
+    #[func]
+    /// The names of every save slot on disk
+    pub fn list_slots(&self) -> PackedStringArray {
+        DirAccess::get_files_at(Self::SAVE_DIR)
+            .as_slice()
+            .iter()
+            .filter_map(|file| file.to_string().strip_suffix(".ron").map(GString::from))
+            .collect()
+    }
+
+    #[func]
+    /// The metadata of a slot as a dictionary with `slot`, `playtime`, `timestamp`, `scene` and `location`. Empty if the slot can't be read
+    pub fn get_slot_metadata(&self, slot: GString) -> Dictionary {
+        let Some(document) = Self::read_slot(&slot) else {
+            return Dictionary::new();
+        };
+        let meta = document.meta;
+        dict! {
+            "slot": meta.slot,
+            "playtime": meta.playtime,
+            "timestamp": meta.timestamp,
+            "scene": meta.scene,
+            "location": meta.location,
+        }
+    }
+
+    #[func]
+    /// Seconds played, including playtime from the loaded save
+    pub fn get_playtime(&self) -> f64 {
+        let session = Time::singleton().get_ticks_msec() - self.session_start_msec;
+        self.playtime_offset + session as f64 / 1000.0
+    }
+
+    #[func]
+    /// Starts counting playtime from zero, e.g. when starting a new game
+    pub fn reset_playtime(&mut self) {
+        self.playtime_offset = 0.0;
+        self.session_start_msec = Time::singleton().get_ticks_msec();
+    }
+
+    /// Watches the root for the scene [SaveSystem::load_game] changed to. The scene is only added once the tree flushes the change, after any deferred calls made now
+    fn wait_for_scene(&mut self, slot: GString, scene: GString) {
+        self.stop_waiting_for_scene();
+        let Some(mut root) = engine::get_scene_tree().and_then(|tree| tree.get_root()) else {
+            return;
+        };
+        self.loading = Some((slot, scene));
+        root.connect(
+            "child_entered_tree",
+            &Callable::from_object_method(&self.to_gd(), "on_scene_entered"),
+        );
+    }
+
+    fn stop_waiting_for_scene(&mut self) {
+        self.loading = None;
+        let Some(mut root) = engine::get_scene_tree().and_then(|tree| tree.get_root()) else {
+            return;
+        };
+        let callable = Callable::from_object_method(&self.to_gd(), "on_scene_entered");
+        if root.is_connected("child_entered_tree", &callable) {
+            root.disconnect("child_entered_tree", &callable);
+        }
+    }
+
+    /// Hands out the loaded data for a node that was just instantiated. Each entry is only handed out once
+    pub fn take_pending(&mut self, save_id: &str) -> Option<NodeSaveData> {
+        self.pending.remove(save_id)
+    }
+
+    fn make_metadata(&self, slot: &GString) -> SaveMetadata {
+        let scene = engine::get_scene_tree().and_then(|tree| tree.get_current_scene());
+        let location = if self.location_name.is_empty() {
+            scene
+                .as_ref()
+                .map(|scene| scene.get_name().to_string())
+                .unwrap_or_default()
+        } else {
+            self.location_name.to_string()
+        };
+        SaveMetadata {
+            slot: slot.to_string(),
+            playtime: self.get_playtime(),
+            timestamp: Time::singleton().get_unix_time_from_system() as i64,
+            scene: scene
+                .map(|scene| scene.get_scene_file_path().to_string())
+                .unwrap_or_default(),
+            location,
+        }
+    }
+
+    /// The file of a slot inside [SaveSystem::SAVE_DIR]. None with an error if the name could point anywhere else
+    fn slot_path(slot: &GString) -> Option<GString> {
+        let name = slot.to_string();
+        let is_valid = !name.is_empty()
+            && !name.starts_with('.')
+            && !name
+                .chars()
+                .any(|c| c.is_control() || "/\\:*?\"<>|".contains(c));
+        if !is_valid {
+            godot_error!("Invalid save slot name \"{}\"", name);
+            return None;
+        }
+        Some(format!("{}/{}.ron", Self::SAVE_DIR, name).into())
+    }
+
+    fn read_slot(slot: &GString) -> Option<SaveDocument> {
+        let path = Self::slot_path(slot)?;
+        let Some(file) = FileAccess::open(&path, ModeFlags::READ) else {
+            godot_error!(
+                "Failed to open save {}: {}",
+                path,
+                FileAccess::get_open_error().godot_name()
+            );
+            return None;
+        };
+        match SaveDocument::parse(&file.get_as_text().to_string()) {
+            Ok(document) => Some(document),
+            Err(err) => {
+                godot_error!("Failed to parse save {}: {}", path, err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::versioning::testing::{rename_field, with_migration};
+
+    const LEGACY: &str = include_str!("../../tests/fixtures/save/legacy.ron");
+    const CURRENT: &str = include_str!("../../tests/fixtures/save/current.ron");
+    /// Written at the current version, but with `meta.location` still called `area`
+    const OLDER: &str = include_str!("../../tests/fixtures/save/older.ron");
+
+    fn assert_chest(document: &SaveDocument) {
+        assert_eq!(document.meta.slot, "slot1");
+        assert_eq!(document.meta.scene, "res://level/main_level.tscn");
+        assert_eq!(document.meta.location, "Cellar");
+        let chest = &document.nodes["Chest"];
+        assert_eq!(
+            chest.transform_3d.map(|transform| transform.origin),
+            Some(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(chest.properties["opened"], "true");
+        let inventory = chest.inventory.as_ref().unwrap();
+        assert_eq!(inventory.stacks[0].item, "res://items/key.tres");
+        assert_eq!(inventory.stacks[0].count, 1);
+    }
+
+    #[test]
+    fn parses_legacy_save() {
+        assert_chest(&SaveDocument::parse(LEGACY).unwrap());
+    }
+
+    #[test]
+    fn parses_current_save() {
+        assert_chest(&SaveDocument::parse(CURRENT).unwrap());
+    }
+
+    #[test]
+    fn migrates_older_save() {
+        fn rename_area(data: ron::Value) -> Result<ron::Value, String> {
+            rename_field(data, &["meta"], "area", "location")
+        }
+        assert!(SaveDocument::parse(OLDER).is_err());
+        let format = with_migration(&SAVE_FORMAT, rename_area);
+        assert_chest(&format.read::<SaveDocument>(OLDER).unwrap());
+    }
+
+    #[test]
+    fn round_trips_current_save() {
+        let document = SaveDocument::parse(CURRENT).unwrap();
+        let text = SAVE_FORMAT.write(&document).unwrap();
+        assert_chest(&SaveDocument::parse(&text).unwrap());
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use godot::{
+    global::{str_to_var, var_to_str},
+    obj::WithBaseField,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    component_utils::{RustyComponent, RustyComponents},
+    engine_helpers::{engine, CrateSingleton},
+    inventory::{Inventory, InventoryData},
+};
+
+use super::SaveSystem;
+
+/// Everything saved for a single [Persistent] node
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NodeSaveData {
+    pub transform_3d: Option<Transform3D>,
+    pub transform_2d: Option<Transform2D>,
+    /// Saved properties of the parent, each stored in godot's `var_to_str` format so any variant survives the round trip
+    pub properties: BTreeMap<String, String>,
+    /// Whatever the parent returned from `_save_data`, in `var_to_str` format
+    pub custom: Option<String>,
+    /// The contents of a sibling [Inventory]
+    pub inventory: Option<InventoryData>,
+}
+
+#[derive(Debug, GodotClass)]
+#[class(base=Node, init)]
+/// Opts the parent node into the [SaveSystem]
+///
+/// Saves the parent's transform, the listed properties, a sibling [Inventory], and anything returned by a `_save_data() -> Variant` method on the parent, which gets it back through `_load_data(data)`
+///
+/// Only nodes that exist when loading are restored. Freed nodes aren't recorded, so a node freed before saving, e.g. a picked up item, is back after loading its scene. Track removed nodes on something that stays around, e.g. a list of collected ids saved by the level, and free them again from its `_load_data`
+pub struct Persistent {
+    #[export]
+    /// A unique id for this node within the save. Defaults to the parent's path from the current scene
+    save_id: GString,
+    #[export]
+    #[init(val = true)]
+    save_transform: bool,
+    #[export]
+    /// Names of properties on the parent to save
+    properties: PackedStringArray,
+    base: Base<Node>,
+}
+
+impl RustyComponent<Node> for Persistent {}
+
+#[godot_api]
+impl INode for Persistent {
+    fn ready(&mut self) {
+        self.on_ready();
+        let Some(mut save_system) = SaveSystem::singleton() else {
+            return;
+        };
+        let id = self.resolve_save_id().to_string();
+        let pending = save_system.bind_mut().take_pending(&id);
+        if let Some(data) = pending {
+            self.apply(data);
+        }
+    }
+}
+
+#[godot_api]
+impl Persistent {
+    #[func]
+    /// The id this node is saved under, falling back to the parent's path when `save_id` is empty
+    pub fn resolve_save_id(&self) -> GString {
+        if !self.save_id.is_empty() {
+            return self.save_id.clone();
+        }
+        let Some(parent) = self.base().get_parent() else {
+            return GString::new();
+        };
+        match engine::get_scene_tree().and_then(|tree| tree.get_current_scene()) {
+            Some(scene) => scene.get_path_to(&parent).into(),
+            None => parent.get_path().into(),
+        }
+    }
+
+    /// Gathers the parent's state, keyed by save id
+    pub fn collect(&self) -> Option<(String, NodeSaveData)> {
+        let mut parent = self.base().get_parent()?;
+        let mut data = NodeSaveData::default();
+        if self.save_transform {
+            if let Ok(node) = parent.clone().try_cast::<Node3D>() {
+                data.transform_3d = Some(node.get_transform());
+            } else if let Ok(node) = parent.clone().try_cast::<Node2D>() {
+                data.transform_2d = Some(node.get_transform());
+            }
+        }
+        for property in self.properties.as_slice() {
+            let value = parent.get(&StringName::from(property));
+            data.properties
+                .insert(property.to_string(), var_to_str(&value).to_string());
+        }
+        if parent.has_method("_save_data") {
+            let custom = parent.call("_save_data", &[]);
+            data.custom = Some(var_to_str(&custom).to_string());
+        }
+        data.inventory = RustyComponents::get_component::<Inventory>(&parent)
+            .map(|inventory| inventory.bind().save_data());
+        Some((self.resolve_save_id().to_string(), data))
+    }
+
+    /// Restores previously collected state onto the parent
+    pub fn apply(&mut self, data: NodeSaveData) {
+        let Some(mut parent) = self.base().get_parent() else {
+            return;
+        };
+        if let (Some(transform), Ok(mut node)) =
+            (data.transform_3d, parent.clone().try_cast::<Node3D>())
+        {
+            node.set_transform(transform);
+        }
+        if let (Some(transform), Ok(mut node)) =
+            (data.transform_2d, parent.clone().try_cast::<Node2D>())
+        {
+            node.set_transform(transform);
+        }
+        for (property, value) in data.properties {
+            parent.set(&StringName::from(property.as_str()), &str_to_var(&value));
+        }
+        if let Some(custom) = data.custom {
+            if parent.has_method("_load_data") {
+                parent.call("_load_data", &[str_to_var(&custom)]);
+            }
+        }
+        if let (Some(saved), Some(mut inventory)) = (
+            data.inventory,
+            RustyComponents::get_component::<Inventory>(&parent),
+        ) {
+            inventory.bind_mut().load_data(saved);
+        }
+    }
+}
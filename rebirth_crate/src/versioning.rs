@@ -0,0 +1,297 @@
+//! Versioned RON documents
+//!
+//! Everything the crate persists is written inside an envelope recording the version of the format it was written with:
+//!
+//! ```ron
+//! (
+//!     version: 2,
+//!     data: ( ... ),
+//! )
+//! ```
+//!
+//! When a persisted struct changes, push a [Migration] onto its [VersionedFormat]. Older documents are then upgraded one version at a time, as untyped RON values, before being deserialized into the current struct.
+//!
+//! Untyped RON values can't tell enum variants apart, so persisted structs should store enums by their ordinal.
+
+use std::fmt;
+
+use ron::Value;
+use serde::{de::DeserializeOwned, Serialize};
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+/// Upgrades a document's data from version N to N+1
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// A persisted format along with every migration it has gone through
+///
+/// Versions start at 1 and `migrations[i]` upgrades version `i + 1` to `i + 2`, so registering a migration is all it takes to bump the version
+pub struct VersionedFormat {
+    migrations: &'static [Migration],
+}
+
+#[derive(Debug)]
+pub enum VersionError {
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    Deserialize(ron::Error),
+    /// The document was written by a newer build than this one
+    TooNew {
+        found: u32,
+        current: u32,
+    },
+    Migration {
+        from: u32,
+        message: String,
+    },
+}
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionError::Parse(err) => write!(f, "invalid RON: {}", err),
+            VersionError::Serialize(err) => write!(f, "failed to serialize: {}", err),
+            VersionError::Deserialize(err) => write!(f, "unexpected data: {}", err),
+            VersionError::TooNew { found, current } => write!(
+                f,
+                "written by a newer version ({}) than supported ({})",
+                found, current
+            ),
+            VersionError::Migration { from, message } => write!(
+                f,
+                "failed to migrate from version {} to {}: {}",
+                from,
+                from + 1,
+                message
+            ),
+        }
+    }
+}
+
+impl VersionedFormat {
+    const VERSION_KEY: &'static str = "version";
+    const DATA_KEY: &'static str = "data";
+
+    pub const fn new(migrations: &'static [Migration]) -> Self {
+        Self { migrations }
+    }
+
+    /// The version documents are currently written at
+    pub fn version(&self) -> u32 {
+        self.migrations.len() as u32 + 1
+    }
+
+    /// Serializes the data inside an envelope tagged with the current version
+    pub fn write<T: Serialize>(&self, data: &T) -> Result<String, VersionError> {
+        let envelope = Envelope {
+            version: self.version(),
+            data,
+        };
+        ron::ser::to_string_pretty(&envelope, ron::ser::PrettyConfig::default())
+            .map_err(VersionError::Serialize)
+    }
+
+    /// Parses a document written at any version up to the current one, migrating it as needed
+    ///
+    /// Documents without an envelope are treated as version 1
+    pub fn read<T: DeserializeOwned>(&self, text: &str) -> Result<T, VersionError> {
+        let value = ron::from_str::<Value>(text).map_err(VersionError::Parse)?;
+        let (version, data) = Self::open_envelope(value);
+        self.migrate(data, version)?
+            .into_rust::<T>()
+            .map_err(VersionError::Deserialize)
+    }
+
+    /// Runs every migration from the given version up to the current one
+    pub fn migrate(&self, mut data: Value, from: u32) -> Result<Value, VersionError> {
+        let current = self.version();
+        if from > current {
+            return Err(VersionError::TooNew {
+                found: from,
+                current,
+            });
+        }
+        for version in from.max(1)..current {
+            let migration = self.migrations[version as usize - 1];
+            data = migration(data).map_err(|message| VersionError::Migration {
+                from: version,
+                message,
+            })?;
+        }
+        Ok(data)
+    }
+
+    fn open_envelope(value: Value) -> (u32, Value) {
+        let Value::Map(mut map) = value else {
+            return (1, value);
+        };
+        let version_key = Value::String(Self::VERSION_KEY.into());
+        let data_key = Value::String(Self::DATA_KEY.into());
+        let version = match map.iter().find(|(key, _)| **key == version_key) {
+            Some((_, Value::Number(number))) => number.as_i64(),
+            _ => None,
+        };
+        match (version, map.len()) {
+            (Some(version), 2) => match map.remove(&data_key) {
+                Some(data) => (version.max(0) as u32, data),
+                None => (1, Value::Map(map)),
+            },
+            _ => (1, Value::Map(map)),
+        }
+    }
+}
+
+/// Helpers for testing real formats against migrations they don't have yet
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+
+    /// The format with one more migration after its own, so documents at its current version have to be migrated
+    pub fn with_migration(format: &VersionedFormat, next: Migration) -> VersionedFormat {
+        let migrations = format.migrations.iter().copied().chain([next]).collect();
+        VersionedFormat::new(Vec::leak(migrations))
+    }
+
+    /// Renames a field of the struct found by following `path` from the document's data
+    pub fn rename_field(data: Value, path: &[&str], from: &str, to: &str) -> Result<Value, String> {
+        let Value::Map(mut map) = data else {
+            return Err("expected a struct".into());
+        };
+        match path.split_first() {
+            Some((field, rest)) => {
+                let key = Value::String(field.to_string());
+                let inner = map.remove(&key).ok_or(format!("missing {}", field))?;
+                map.insert(key, rename_field(inner, rest, from, to)?);
+            }
+            None => {
+                let value = map
+                    .remove(&Value::String(from.into()))
+                    .ok_or(format!("missing {}", from))?;
+                map.insert(Value::String(to.into()), value);
+            }
+        }
+        Ok(Value::Map(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    /// Version 3 of the test format. Version 1 called `amount` `count` and version 2 had no `weight`
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        name: String,
+        amount: i32,
+        weight: f32,
+    }
+
+    const LEGACY_V1: &str = include_str!("../tests/fixtures/versioning/legacy_v1.ron");
+    const ENVELOPED_V2: &str = include_str!("../tests/fixtures/versioning/enveloped_v2.ron");
+    const CURRENT_V3: &str = include_str!("../tests/fixtures/versioning/current_v3.ron");
+    const TOO_NEW_V4: &str = include_str!("../tests/fixtures/versioning/too_new_v4.ron");
+
+    const ITEM_FORMAT: VersionedFormat =
+        VersionedFormat::new(&[rename_count_to_amount, add_weight]);
+
+    fn fields(data: Value) -> Result<ron::Map, String> {
+        match data {
+            Value::Map(map) => Ok(map),
+            other => Err(format!("expected a struct, found {:?}", other)),
+        }
+    }
+
+    fn rename_count_to_amount(data: Value) -> Result<Value, String> {
+        let mut map = fields(data)?;
+        let count = map
+            .remove(&Value::String("count".into()))
+            .ok_or("missing count")?;
+        map.insert(Value::String("amount".into()), count);
+        Ok(Value::Map(map))
+    }
+
+    fn add_weight(data: Value) -> Result<Value, String> {
+        let mut map = fields(data)?;
+        map.insert(
+            Value::String("weight".into()),
+            Value::Number(ron::Number::from(1.0)),
+        );
+        Ok(Value::Map(map))
+    }
+
+    fn rusty_key(weight: f32) -> Item {
+        Item {
+            name: "Rusty key".into(),
+            amount: 3,
+            weight,
+        }
+    }
+
+    #[test]
+    fn reads_legacy_document_without_envelope() {
+        let item = ITEM_FORMAT.read::<Item>(LEGACY_V1).unwrap();
+        assert_eq!(item, rusty_key(1.0));
+    }
+
+    #[test]
+    fn reads_current_document() {
+        let item = ITEM_FORMAT.read::<Item>(CURRENT_V3).unwrap();
+        assert_eq!(item, rusty_key(0.5));
+    }
+
+    #[test]
+    fn migrates_one_version_at_a_time() {
+        let item = ITEM_FORMAT.read::<Item>(ENVELOPED_V2).unwrap();
+        assert_eq!(item, rusty_key(1.0));
+
+        // Only the migrations after the document's version run
+        let older = VersionedFormat::new(&[rename_count_to_amount]);
+        let value = older.migrate(ron::from_str(LEGACY_V1).unwrap(), 1).unwrap();
+        let keys = fields(value)
+            .unwrap()
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        assert!(keys.contains(&Value::String("amount".into())));
+        assert!(!keys.contains(&Value::String("weight".into())));
+    }
+
+    #[test]
+    fn writes_current_version() {
+        let text = ITEM_FORMAT.write(&rusty_key(0.5)).unwrap();
+        assert!(text.contains("version: 3"));
+        assert_eq!(ITEM_FORMAT.read::<Item>(&text).unwrap(), rusty_key(0.5));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        match ITEM_FORMAT.read::<Item>(TOO_NEW_V4) {
+            Err(VersionError::TooNew { found, current }) => {
+                assert_eq!(found, 4);
+                assert_eq!(current, 3);
+            }
+            other => panic!("expected TooNew, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_failed_migrations() {
+        fn fail(_: Value) -> Result<Value, String> {
+            Err("no weight for this item".into())
+        }
+        let format = VersionedFormat::new(&[rename_count_to_amount, fail]);
+        match format.read::<Item>(LEGACY_V1) {
+            Err(VersionError::Migration { from, message }) => {
+                assert_eq!(from, 2);
+                assert_eq!(message, "no weight for this item");
+            }
+            other => panic!("expected a migration error, got {:?}", other),
+        }
+    }
+}
@@ -5,6 +5,9 @@ var mouse_delta := Vector2.ZERO
 
 func _ready() -> void:
 	EventDepot.add_listener(mouse_motion_relay_event, mouse_motion_event)
+	var accessibility: AccessibilitySettings = GameSettings.accessibility
+	$Camera3D.fov = accessibility.field_of_view
+	accessibility.field_of_view_changed.connect(func(degrees: float): $Camera3D.fov = degrees)
 
 func mouse_motion_event(relative: Vector2):
 	mouse_delta += relative
@@ -8,6 +8,9 @@ extends StateMachineState
 @export var JUMP_VELOCITY := 4.5
 @export var look_angle_limit := 75.0
 
+## Seconds the look stick has been held far enough to accelerate, see ControlsSettings
+var look_accelerating_for := 0.0
+
 func _state_enter() -> void:
 	Input.mouse_mode = Input.MOUSE_MODE_CAPTURED
 	player.mouse_delta = Vector2.ZERO
@@ -40,8 +43,14 @@ func _state_physics(delta: float) -> void:
 
 
 func _state_input(event: InputEvent) -> bool:
+	var toggle_hold: bool = GameSettings.accessibility.toggle_hold_actions
 	if event.is_action_pressed("interact"):
-		interactor.do_interact()
+		if toggle_hold and interactor.is_holding():
+			interactor.cancel_interact()
+		else:
+			interactor.do_interact()
+	if event.is_action_released("interact") and not toggle_hold:
+		interactor.cancel_interact()
 	if event.is_action_pressed("escape"):
 		if Input.mouse_mode == Input.MOUSE_MODE_CAPTURED:
 			Input.mouse_mode = Input.MOUSE_MODE_VISIBLE
@@ -51,12 +60,16 @@ func _state_input(event: InputEvent) -> bool:
 
 
 func _move_cam(delta: float):
-	var look_vec := Input.get_vector("look_left", "look_right", "look_down", "look_up")
-	if look_vec.length_squared() < 0.3:
-		look_vec = player.mouse_delta # mul mouse sensitivity
-		player.mouse_delta = Vector2.ZERO
+	var controls: ControlsSettings = GameSettings.controls
+	var stick := Input.get_vector("look_left", "look_right", "look_down", "look_up")
+	if controls.is_gamepad_accelerating(stick):
+		look_accelerating_for += delta
 	else:
-		look_vec *= 10.0 # gamepad sensitivity
+		look_accelerating_for = 0.0
+	var look_vec := controls.get_gamepad_look(stick, look_accelerating_for)
+	if stick.is_zero_approx():
+		look_vec = controls.get_mouse_look(player.mouse_delta)
+		player.mouse_delta = Vector2.ZERO
 	look_vec *= -1
 	player.rotate_y(look_vec.x * delta)
 	camera.rotate_x(look_vec.y * delta)
@@ -0,0 +1,45 @@
+extends SceneTree
+## Headless checks that scripts can apply the graphics settings to specific targets through
+## GraphicsSettings.apply_to and read back what was applied. Run through ../test.sh.
+
+var failures := 0
+
+
+func _initialize() -> void:
+	_run.call_deferred()
+
+
+func _run() -> void:
+	var graphics: GraphicsSettings = GameSettings.graphics
+
+	var world_environment := WorldEnvironment.new()
+	world_environment.environment = Environment.new()
+	var camera := Camera3D.new()
+	root.add_child(world_environment)
+	root.add_child(camera)
+	await process_frame
+
+	var applied := graphics.apply_to(null, world_environment, camera, true)
+	_expect(applied & GraphicsSettings.APPLIED_ENVIRONMENT != 0, "environment is applied")
+	_expect(applied & GraphicsSettings.APPLIED_CAMERA_ATTRIBUTES != 0, "camera attributes are applied")
+	_expect(applied & GraphicsSettings.APPLIED_VIEWPORT == 0, "viewport is skipped when not given")
+	_expect(world_environment.environment.sdfgi_enabled == graphics.sdfgi_enabled, "environment matches the settings")
+	_expect(camera.attributes != null, "missing camera attributes are created")
+
+	applied = graphics.apply_to(root, null, null, false)
+	_expect(applied & GraphicsSettings.APPLIED_VIEWPORT != 0, "viewport is applied")
+	_expect(root.msaa_3d == graphics.msaa, "viewport matches the settings")
+
+	if failures > 0:
+		printerr("%d graphics check(s) failed" % failures)
+	else:
+		print("All graphics checks passed")
+	quit(1 if failures > 0 else 0)
+
+
+func _expect(condition: bool, description: String) -> void:
+	if condition:
+		print("ok   %s" % description)
+		return
+	failures += 1
+	printerr("FAIL %s" % description)
@@ -0,0 +1,64 @@
+extends SceneTree
+## Headless checks that rebound actions survive the round trip through InputSettings binding
+## codes and match input from every device, not just the first one. Run through ../test.sh.
+
+const ACTION := &"interact"
+
+var failures := 0
+
+
+func _initialize() -> void:
+	var settings := InputSettings.new()
+
+	var button := InputEventJoypadButton.new()
+	button.button_index = JOY_BUTTON_MISC1
+	button.device = 1
+	_check_round_trip(settings, "joy_button", button)
+
+	var axis := InputEventJoypadMotion.new()
+	axis.axis = JOY_AXIS_TRIGGER_LEFT
+	axis.axis_value = 1.0
+	axis.device = 2
+	_check_round_trip(settings, "joy_axis", axis)
+
+	var key := InputEventKey.new()
+	key.physical_keycode = KEY_F9
+	_check_round_trip(settings, "key", key)
+
+	var mouse := InputEventMouseButton.new()
+	mouse.button_index = MOUSE_BUTTON_XBUTTON2
+	_check_round_trip(settings, "mouse", mouse)
+
+	InputMap.load_from_project_settings()
+	if failures > 0:
+		printerr("%d input check(s) failed" % failures)
+	else:
+		print("All input checks passed")
+	quit(1 if failures > 0 else 0)
+
+
+func _check_round_trip(settings: InputSettings, label: String, event: InputEvent) -> void:
+	var code := settings.get_binding_code(event)
+	_expect(label, settings.rebind(ACTION, event), "%s is bound" % code)
+	_expect(label, code in settings.get_action_bindings(ACTION), "binding is stored")
+	settings.apply()
+
+	var bound := InputMap.action_get_events(ACTION).filter(func(e: InputEvent) -> bool:
+		return settings.get_binding_code(e) == code)
+	_expect(label, bound.size() == 1, "input map has the rebuilt event")
+	if bound.size() == 1:
+		_expect(label, bound[0].device == -1, "rebuilt event matches every device")
+	for device in [0, 1, 3]:
+		var pressed: InputEvent = event.duplicate()
+		pressed.device = device
+		if pressed is InputEventJoypadButton or pressed is InputEventKey or pressed is InputEventMouseButton:
+			pressed.pressed = true
+		_expect(label, InputMap.event_is_action(pressed, ACTION), "device %d triggers the action" % device)
+
+
+func _expect(label: String, condition: bool, description: String) -> void:
+	if condition:
+		print("ok   %s: %s" % [label, description])
+		return
+	failures += 1
+	printerr("FAIL %s: %s" % [label, description])
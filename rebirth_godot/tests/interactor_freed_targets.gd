@@ -0,0 +1,77 @@
+extends SceneTree
+## Headless checks that an Interactor drops a targeted interactable once it is freed or
+## removed from the tree, including while it is still alive outside of it. Run through ../test.sh, which also fails on any logged error.
+
+var failures := 0
+var last_changed: Array = []
+
+
+func _initialize() -> void:
+	_run.call_deferred()
+
+
+func _run() -> void:
+	await _check_drops_target("queue_free", func(target: Node3D) -> void:
+		target.queue_free())
+	await _check_drops_target("remove_child", func(target: Node3D) -> void:
+		target.get_parent().remove_child(target)
+		target.free())
+	await _check_drops_target("removed but alive", func(target: Node3D) -> void:
+		target.get_parent().remove_child(target))
+	if failures > 0:
+		printerr("%d interactor check(s) failed" % failures)
+	else:
+		print("All interactor checks passed")
+	quit(1 if failures > 0 else 0)
+
+
+func _check_drops_target(label: String, drop: Callable) -> void:
+	var world := Node3D.new()
+	root.add_child(world)
+
+	var interactor := Interactor.new()
+	interactor.target_position = Vector3(0, 0, -5)
+	interactor.interactable_changed.connect(_on_interactable_changed)
+	world.add_child(interactor)
+
+	var target := StaticBody3D.new()
+	var shape := CollisionShape3D.new()
+	shape.shape = BoxShape3D.new()
+	target.add_child(shape)
+	target.add_child(InteractionComponent.new())
+	target.position = Vector3(0, 0, -2)
+	world.add_child(target)
+
+	last_changed = []
+	await _physics_frames(3)
+	_expect(label, not last_changed.is_empty() and last_changed[0] == target, "target is selected")
+
+	drop.call(target)
+	await _physics_frames(3)
+	_expect(label, not last_changed.is_empty() and last_changed[0] == null, "interactable_changed(null) is emitted")
+	_expect(label, interactor.get_interaction() == null, "no interaction remains")
+	_expect(label, interactor.get_prompt().is_empty(), "prompt is cleared")
+	if is_instance_valid(target):
+		# Only free targets that left the tree once the interactor has dropped them
+		_expect(label, not target.is_inside_tree(), "target is still alive outside the tree")
+		target.free()
+
+	world.queue_free()
+	await _physics_frames(1)
+
+
+func _on_interactable_changed(interactable: Node, prompt: String, verbs: PackedStringArray) -> void:
+	last_changed = [interactable, prompt, verbs]
+
+
+func _physics_frames(count: int) -> void:
+	for i in count:
+		await physics_frame
+
+
+func _expect(label: String, condition: bool, description: String) -> void:
+	if condition:
+		print("ok   %s: %s" % [label, description])
+		return
+	failures += 1
+	printerr("FAIL %s: %s" % [label, description])
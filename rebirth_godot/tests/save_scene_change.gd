@@ -0,0 +1,59 @@
+extends SceneTree
+## Headless check that loading a save made in another scene changes back to it, restores its
+## Persistent nodes and only then emits game_loaded. Run through ../test.sh.
+
+const SLOT := "headless_scene_change"
+const TARGET_SCENE := "res://tests/scenes/save_target.tscn"
+const OTHER_SCENE := "res://tests/scenes/save_other.tscn"
+const SAVED_POSITION := Vector3(1, 2, 3)
+
+var failures := 0
+
+
+func _initialize() -> void:
+	_run.call_deferred()
+
+
+func _run() -> void:
+	change_scene_to_file(TARGET_SCENE)
+	await _frames(2)
+	var box: Node3D = current_scene.get_node("Box")
+	box.position = SAVED_POSITION
+	_expect(SaveSystem.save_game(SLOT), "save is written")
+
+	change_scene_to_file(OTHER_SCENE)
+	await _frames(2)
+	_expect(current_scene.scene_file_path == OTHER_SCENE, "other scene is current")
+
+	var loaded := []
+	SaveSystem.game_loaded.connect(func(slot: String) -> void:
+		loaded.append([slot, current_scene.scene_file_path, current_scene.get_node("Box").position]))
+	_expect(SaveSystem.load_game(SLOT), "load starts")
+	await _frames(3)
+
+	_expect(loaded.size() == 1, "game_loaded is emitted once")
+	if loaded.size() == 1:
+		_expect(loaded[0][0] == SLOT, "game_loaded has the slot")
+		_expect(loaded[0][1] == TARGET_SCENE, "game_loaded is emitted once the saved scene is current")
+		_expect(loaded[0][2] == SAVED_POSITION, "box is restored before game_loaded")
+	_expect(current_scene.get_node("Box").position == SAVED_POSITION, "box keeps its restored position")
+
+	SaveSystem.delete_save(SLOT)
+	if failures > 0:
+		printerr("%d save check(s) failed" % failures)
+	else:
+		print("All save checks passed")
+	quit(1 if failures > 0 else 0)
+
+
+func _frames(count: int) -> void:
+	for i in count:
+		await process_frame
+
+
+func _expect(condition: bool, description: String) -> void:
+	if condition:
+		print("ok   %s" % description)
+		return
+	failures += 1
+	printerr("FAIL %s" % description)
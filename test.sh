@@ -0,0 +1,18 @@
+#!/usr/bin/env bash
+# Runs every headless Godot check in rebirth_godot/tests, failing on any failed check or logged error
+set -euo pipefail
+
+: "${GODOT4_BIN:?set GODOT4_BIN to a Godot 4 executable}"
+cd "$(dirname "$0")/rebirth_godot"
+
+failed=0
+for script in tests/*.gd; do
+	echo "== $script"
+	status=0
+	output=$("$GODOT4_BIN" --headless --path . -s "res://$script" 2>&1) || status=$?
+	echo "$output"
+	if [ "$status" -ne 0 ] || grep -q "ERROR" <<<"$output"; then
+		failed=1
+	fi
+done
+exit "$failed"